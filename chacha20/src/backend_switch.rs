@@ -0,0 +1,163 @@
+//! Runtime backend selection, intended for testing backend parity in a single
+//! build.
+
+use crate::{backends, ChaChaCore, Rounds, Variant};
+
+#[cfg(feature = "cipher")]
+use cipher::{consts::U64, StreamCipherClosure};
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(chacha20_force_soft),
+    not(chacha20_force_sse2)
+))]
+cpufeatures::new!(avx2_cpuid, "avx2");
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    not(chacha20_force_soft),
+    not(chacha20_force_avx2)
+))]
+cpufeatures::new!(sse2_cpuid, "sse2");
+
+/// ChaCha backend implementations which can be forced at runtime with
+/// [`ChaChaCore::with_backend`], or `with_backend` on the ChaCha RNGs.
+///
+/// This is a testing aid and not part of the stable API: normally the best
+/// available backend is selected automatically.
+#[cfg_attr(docsrs, doc(cfg(feature = "dev-backend-switch")))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChaChaBackend {
+    /// Portable software implementation.
+    Soft,
+    /// SSE2 implementation for x86/x86_64.
+    Sse2,
+    /// AVX2 implementation for x86/x86_64.
+    Avx2,
+    /// NEON implementation for little-endian aarch64.
+    Neon,
+}
+
+impl ChaChaBackend {
+    /// All backends, whether or not they are available on this target.
+    pub const ALL: [Self; 4] = [Self::Soft, Self::Sse2, Self::Avx2, Self::Neon];
+
+    /// Returns `true` if this backend is compiled in and supported by the CPU.
+    ///
+    /// Backends excluded by one of the `chacha20_force_*` configuration flags
+    /// are not compiled in, and so are never available.
+    pub fn is_available(self) -> bool {
+        match self {
+            Self::Soft => true,
+            #[cfg(all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                not(chacha20_force_soft),
+                not(chacha20_force_avx2)
+            ))]
+            Self::Sse2 => sse2_cpuid::get(),
+            #[cfg(all(
+                any(target_arch = "x86", target_arch = "x86_64"),
+                not(chacha20_force_soft),
+                not(chacha20_force_sse2)
+            ))]
+            Self::Avx2 => avx2_cpuid::get(),
+            #[cfg(all(
+                target_arch = "aarch64",
+                target_feature = "neon",
+                target_endian = "little",
+                not(chacha20_force_soft)
+            ))]
+            Self::Neon => true,
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+}
+
+impl<R: Rounds, V: Variant> ChaChaCore<R, V> {
+    /// Force this core to use `backend` instead of the automatically selected
+    /// one.
+    ///
+    /// # Panics
+    ///
+    /// If `backend` is not [available][ChaChaBackend::is_available].
+    #[cfg_attr(docsrs, doc(cfg(feature = "dev-backend-switch")))]
+    pub fn with_backend(mut self, backend: ChaChaBackend) -> Self {
+        self.set_backend(backend);
+        self
+    }
+
+    pub(crate) fn set_backend(&mut self, backend: ChaChaBackend) {
+        assert!(
+            backend.is_available(),
+            "ChaCha backend {backend:?} is not available"
+        );
+        self.backend = Some(backend);
+    }
+}
+
+#[cfg(feature = "cipher")]
+pub(crate) fn process_with<R, V, F>(core: &mut ChaChaCore<R, V>, backend: ChaChaBackend, f: F)
+where
+    R: Rounds,
+    V: Variant,
+    F: StreamCipherClosure<BlockSize = U64>,
+{
+    // SAFETY: `with_backend` only accepts backends supported by the CPU
+    #[allow(unreachable_patterns)]
+    match backend {
+        ChaChaBackend::Soft => f.call(&mut backends::soft::Backend(core)),
+        #[cfg(all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            not(chacha20_force_soft),
+            not(chacha20_force_avx2)
+        ))]
+        ChaChaBackend::Sse2 => unsafe { backends::sse2::inner::<R, _>(&mut core.state, f) },
+        #[cfg(all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            not(chacha20_force_soft),
+            not(chacha20_force_sse2)
+        ))]
+        ChaChaBackend::Avx2 => unsafe { backends::avx2::inner::<R, _>(&mut core.state, f) },
+        #[cfg(all(
+            target_arch = "aarch64",
+            target_feature = "neon",
+            target_endian = "little",
+            not(chacha20_force_soft)
+        ))]
+        ChaChaBackend::Neon => unsafe { backends::neon::inner::<R, _>(&mut core.state, f) },
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(feature = "rng")]
+pub(crate) fn generate_par_with<R: Rounds, V: Variant>(
+    core: &mut ChaChaCore<R, V>,
+    backend: ChaChaBackend,
+    buffer: &mut [u32; 64],
+) {
+    // SAFETY: `with_backend` only accepts backends supported by the CPU
+    #[allow(unreachable_patterns)]
+    match backend {
+        ChaChaBackend::Soft => backends::soft::Backend(core).gen_ks_blocks(buffer),
+        #[cfg(all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            not(chacha20_force_soft),
+            not(chacha20_force_avx2)
+        ))]
+        ChaChaBackend::Sse2 => unsafe { backends::sse2::rng_inner::<R, V>(core, buffer) },
+        #[cfg(all(
+            any(target_arch = "x86", target_arch = "x86_64"),
+            not(chacha20_force_soft),
+            not(chacha20_force_sse2)
+        ))]
+        ChaChaBackend::Avx2 => unsafe { backends::avx2::rng_inner::<R, V>(core, buffer) },
+        #[cfg(all(
+            target_arch = "aarch64",
+            target_feature = "neon",
+            target_endian = "little",
+            not(chacha20_force_soft)
+        ))]
+        ChaChaBackend::Neon => unsafe { backends::neon::rng_inner::<R, V>(core, buffer) },
+        _ => unreachable!(),
+    }
+}
@@ -7,16 +7,25 @@ cfg_if! {
         cfg_if! {
             if #[cfg(chacha20_force_avx2)] {
                 pub(crate) mod avx2;
+                #[cfg(any(feature = "dynamic-rounds", feature = "dev-backend-switch"))]
+                #[allow(dead_code)]
+                pub(crate) mod soft;
             } else if #[cfg(chacha20_force_sse2)] {
                 pub(crate) mod sse2;
+                #[cfg(any(feature = "dynamic-rounds", feature = "dev-backend-switch"))]
+                #[allow(dead_code)]
+                pub(crate) mod soft;
             } else {
                 pub(crate) mod soft;
                 pub(crate) mod avx2;
                 pub(crate) mod sse2;
             }
         }
-    } else if #[cfg(all(target_arch = "aarch64", target_feature = "neon"))] {
+    } else if #[cfg(all(target_arch = "aarch64", target_feature = "neon", target_endian = "little"))] {
         pub(crate) mod neon;
+        #[cfg(any(feature = "dynamic-rounds", feature = "dev-backend-switch"))]
+        #[allow(dead_code)]
+        pub(crate) mod soft;
     } else {
         pub(crate) mod soft;
     }
@@ -2,6 +2,9 @@
 //!
 //! Adapted from the Crypto++ `chacha_simd` implementation by Jack Lloyd and
 //! Jeffrey Walton (public domain).
+//!
+//! The vectors are stored to the output as bytes, so this backend is only
+//! used on little-endian targets.
 
 use crate::{Rounds, STATE_WORDS};
 use core::{arch::aarch64::*, marker::PhantomData};
@@ -88,15 +91,6 @@ impl<R: Rounds> ParBlocksSizeUser for Backend<R> {
     type ParBlocksSize = U4;
 }
 
-macro_rules! add64 {
-    ($a:expr, $b:expr) => {
-        vreinterpretq_u32_u64(vaddq_u64(
-            vreinterpretq_u64_u32($a),
-            vreinterpretq_u64_u32($b),
-        ))
-    };
-}
-
 /// Evaluates to `a = a + b`, where the operands are u32x4s
 macro_rules! add_assign_vec {
     ($a:expr, $b:expr) => {
@@ -113,7 +107,7 @@ impl<R: Rounds> StreamCipherBackend for Backend<R> {
         self.gen_par_ks_blocks(&mut par);
         *block = par[0];
         unsafe {
-            self.state[3] = add64!(state3, vld1q_u32([1, 0, 0, 0].as_ptr()));
+            self.state[3] = vaddq_u32(state3, vld1q_u32([1, 0, 0, 0].as_ptr()));
         }
     }
 
@@ -126,19 +120,19 @@ impl<R: Rounds> StreamCipherBackend for Backend<R> {
                     self.state[0],
                     self.state[1],
                     self.state[2],
-                    add64!(self.state[3], self.ctrs[0]),
+                    vaddq_u32(self.state[3], self.ctrs[0]),
                 ],
                 [
                     self.state[0],
                     self.state[1],
                     self.state[2],
-                    add64!(self.state[3], self.ctrs[1]),
+                    vaddq_u32(self.state[3], self.ctrs[1]),
                 ],
                 [
                     self.state[0],
                     self.state[1],
                     self.state[2],
-                    add64!(self.state[3], self.ctrs[2]),
+                    vaddq_u32(self.state[3], self.ctrs[2]),
                 ],
             ];
 
@@ -152,7 +146,7 @@ impl<R: Rounds> StreamCipherBackend for Backend<R> {
                     add_assign_vec!(blocks[block][state_row], self.state[state_row]);
                 }
                 if block > 0 {
-                    blocks[block][3] = add64!(blocks[block][3], self.ctrs[block - 1]);
+                    blocks[block][3] = vaddq_u32(blocks[block][3], self.ctrs[block - 1]);
                 }
                 // write blocks to dest
                 for state_row in 0..4 {
@@ -162,7 +156,7 @@ impl<R: Rounds> StreamCipherBackend for Backend<R> {
                     );
                 }
             }
-            self.state[3] = add64!(self.state[3], self.ctrs[3]);
+            self.state[3] = vaddq_u32(self.state[3], self.ctrs[3]);
         }
     }
 }
@@ -205,19 +199,19 @@ impl<R: Rounds> Backend<R> {
                 self.state[0],
                 self.state[1],
                 self.state[2],
-                add64!(self.state[3], self.ctrs[0]),
+                vaddq_u32(self.state[3], self.ctrs[0]),
             ],
             [
                 self.state[0],
                 self.state[1],
                 self.state[2],
-                add64!(self.state[3], self.ctrs[1]),
+                vaddq_u32(self.state[3], self.ctrs[1]),
             ],
             [
                 self.state[0],
                 self.state[1],
                 self.state[2],
-                add64!(self.state[3], self.ctrs[2]),
+                vaddq_u32(self.state[3], self.ctrs[2]),
             ],
         ];
 
@@ -232,7 +226,7 @@ impl<R: Rounds> Backend<R> {
                 add_assign_vec!(blocks[block][state_row], self.state[state_row]);
             }
             if block > 0 {
-                blocks[block][3] = add64!(blocks[block][3], self.ctrs[block - 1]);
+                blocks[block][3] = vaddq_u32(blocks[block][3], self.ctrs[block - 1]);
             }
             // write blocks to buffer
             for state_row in 0..4 {
@@ -243,7 +237,7 @@ impl<R: Rounds> Backend<R> {
             }
             dest_ptr = dest_ptr.add(64);
         }
-        self.state[3] = add64!(self.state[3], self.ctrs[3]);
+        self.state[3] = vaddq_u32(self.state[3], self.ctrs[3]);
     }
 }
 
@@ -302,3 +296,59 @@ unsafe fn cols_to_rows(blocks: &mut [[uint32x4_t; 4]; 4]) {
         extract!(block[3], 1);
     }
 }
+
+#[cfg(all(test, feature = "cipher"))]
+mod tests {
+    use crate::{quarter_round, ChaCha20, CONSTANTS, STATE_WORDS};
+    use cipher::{KeyIvInit, StreamCipher};
+
+    const KEY: [u8; 32] = [0x42; 32];
+    const NONCE: [u8; 12] = [0x24; 12];
+
+    /// Portable keystream reference computed one block at a time.
+    fn soft_keystream(buf: &mut [u8]) {
+        let mut state = [0u32; STATE_WORDS];
+        state[..4].copy_from_slice(&CONSTANTS);
+        for (val, chunk) in state[4..12].iter_mut().zip(KEY.chunks_exact(4)) {
+            *val = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+        for (val, chunk) in state[13..].iter_mut().zip(NONCE.chunks_exact(4)) {
+            *val = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        for (ctr, block) in buf.chunks_mut(64).enumerate() {
+            state[12] = ctr as u32;
+            let mut res = state;
+            for _ in 0..10 {
+                quarter_round(0, 4, 8, 12, &mut res);
+                quarter_round(1, 5, 9, 13, &mut res);
+                quarter_round(2, 6, 10, 14, &mut res);
+                quarter_round(3, 7, 11, 15, &mut res);
+
+                quarter_round(0, 5, 10, 15, &mut res);
+                quarter_round(1, 6, 11, 12, &mut res);
+                quarter_round(2, 7, 8, 13, &mut res);
+                quarter_round(3, 4, 9, 14, &mut res);
+            }
+
+            let mut ks = [0u8; 64];
+            for ((chunk, r), s) in ks.chunks_exact_mut(4).zip(res.iter()).zip(state.iter()) {
+                chunk.copy_from_slice(&r.wrapping_add(*s).to_le_bytes());
+            }
+            block.copy_from_slice(&ks[..block.len()]);
+        }
+    }
+
+    #[test]
+    fn neon_matches_soft() {
+        for len in [1, 63, 64, 65, 255, 256, 257, 1000] {
+            let mut expected = [0u8; 1000];
+            soft_keystream(&mut expected[..len]);
+
+            let mut buf = [0u8; 1000];
+            let mut cipher = ChaCha20::new(&KEY.into(), &NONCE.into());
+            cipher.apply_keystream(&mut buf[..len]);
+            assert_eq!(buf[..len], expected[..len], "length {len}");
+        }
+    }
+}
@@ -44,18 +44,53 @@ impl<R: Rounds, V: Variant> Backend<'_, R, V> {
             let res = run_rounds::<R>(&self.0.state);
             self.0.state[12] = self.0.state[12].wrapping_add(1);
 
-            for (word, val) in buffer[i << 4..(i + 1) << 4].iter_mut().zip(res.iter()) {
-                *word = val.to_le();
-            }
+            // the buffer holds output words, which `BlockRng` serializes as
+            // little-endian itself
+            buffer[i << 4..(i + 1) << 4].copy_from_slice(&res);
+        }
+    }
+}
+
+/// Portable backend performing a number of double rounds selected at runtime.
+#[cfg(feature = "dynamic-rounds")]
+pub(crate) struct DynamicBackend<'a> {
+    pub(crate) state: &'a mut [u32; STATE_WORDS],
+    pub(crate) double_rounds: usize,
+}
+
+#[cfg(feature = "dynamic-rounds")]
+impl BlockSizeUser for DynamicBackend<'_> {
+    type BlockSize = U64;
+}
+
+#[cfg(feature = "dynamic-rounds")]
+impl ParBlocksSizeUser for DynamicBackend<'_> {
+    type ParBlocksSize = U1;
+}
+
+#[cfg(feature = "dynamic-rounds")]
+impl StreamCipherBackend for DynamicBackend<'_> {
+    #[inline(always)]
+    fn gen_ks_block(&mut self, block: &mut Block) {
+        let res = run_rounds_dynamic(self.state, self.double_rounds);
+        self.state[12] = self.state[12].wrapping_add(1);
+
+        for (chunk, val) in block.chunks_exact_mut(4).zip(res.iter()) {
+            chunk.copy_from_slice(&val.to_le_bytes());
         }
     }
 }
 
 #[inline(always)]
 fn run_rounds<R: Rounds>(state: &[u32; STATE_WORDS]) -> [u32; STATE_WORDS] {
+    run_rounds_dynamic(state, R::COUNT)
+}
+
+#[inline(always)]
+fn run_rounds_dynamic(state: &[u32; STATE_WORDS], double_rounds: usize) -> [u32; STATE_WORDS] {
     let mut res = *state;
 
-    for _ in 0..R::COUNT {
+    for _ in 0..double_rounds {
         // column rounds
         quarter_round(0, 4, 8, 12, &mut res);
         quarter_round(1, 5, 9, 13, &mut res);
@@ -4,7 +4,11 @@ pub use cipher::{
     IvSizeUser, KeyIvInit, KeySizeUser, StreamCipherCoreWrapper,
 };
 
-use crate::{variants::Ietf, ChaChaCore, Rounds, R12, R20, R8};
+use crate::{variants::Ietf, ChaChaCore, Rounds, Variant, R12, R20, R8};
+use cipher::StreamCipherSeek;
+use core::fmt;
+#[cfg(feature = "subtle")]
+use subtle::ConstantTimeEq;
 
 /// Key type used by all ChaCha variants.
 pub type Key = Array<u8, U32>;
@@ -19,6 +23,19 @@ pub type ChaCha8 = StreamCipherCoreWrapper<ChaChaCore<R8, Ietf>>;
 pub type ChaCha12 = StreamCipherCoreWrapper<ChaChaCore<R12, Ietf>>;
 
 /// ChaCha20 stream cipher (RFC 8439 version with 96-bit nonce)
+///
+/// The block counter is 32 bits wide, which limits the keystream to
+/// 2<sup>32</sup> - 1 blocks (i.e. just under 256 GiB) per key/nonce pair.
+/// The final counter value is never used, so the counter can not wrap around
+/// and repeat keystream. Once the keystream is exhausted,
+/// [`StreamCipher::try_apply_keystream`][cipher::StreamCipher::try_apply_keystream]
+/// returns [`StreamCipherError`][cipher::StreamCipherError] without modifying
+/// the provided data, while
+/// [`StreamCipher::apply_keystream`][cipher::StreamCipher::apply_keystream] panics.
+///
+/// The keystream can be rewound to its start without re-running key setup
+/// by calling [`StreamCipherSeek::seek`][cipher::StreamCipherSeek::seek] with
+/// `0u32`, which resets the block counter while leaving key and nonce intact.
 pub type ChaCha20 = StreamCipherCoreWrapper<ChaChaCore<R20, Ietf>>;
 
 pub(crate) type Block = Array<u8, U64>;
@@ -36,3 +53,97 @@ impl<R: Rounds> KeyIvInit for ChaChaCore<R, Ietf> {
         ChaChaCore::<R, Ietf>::new(key.as_ref(), iv.as_ref())
     }
 }
+
+impl<R: Rounds> ChaChaCore<R, Ietf> {
+    /// Create a new core, rejecting an all-zero key.
+    ///
+    /// This is an opt-in guard rail against uninitialized key material:
+    /// [`KeyIvInit::new`] accepts any key as required by RFC 8439. The key
+    /// is inspected in constant time. All-zero nonces are not rejected since
+    /// protocols commonly start counter-based nonces at zero.
+    ///
+    /// ```
+    /// use chacha20::{ChaCha20, ChaChaCore, R20};
+    ///
+    /// let nonce = [0x24; 12];
+    /// assert!(ChaChaCore::<R20, _>::new_checked(&[0; 32].into(), &nonce.into()).is_err());
+    ///
+    /// let core = ChaChaCore::new_checked(&[0x42; 32].into(), &nonce.into()).unwrap();
+    /// let cipher = ChaCha20::from_core(core);
+    /// ```
+    #[cfg(feature = "subtle")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "subtle")))]
+    pub fn new_checked(key: &Key, iv: &Nonce) -> Result<Self, WeakKeyError> {
+        if bool::from(key[..].ct_eq(&[0u8; 32])) {
+            return Err(WeakKeyError);
+        }
+        Ok(ChaChaCore::<R, Ietf>::new(key.as_ref(), iv.as_ref()))
+    }
+
+    /// Create a new core with the block counter set to `counter`.
+    ///
+    /// This is equivalent to [`KeyIvInit::new`] followed by seeking to the
+    /// block `counter`, i.e. to byte offset `64 * counter`.
+    ///
+    /// ```
+    /// use chacha20::{ChaCha20, ChaChaCore, R20};
+    ///
+    /// // RFC 8439 starts the encryption at block 1
+    /// let core = ChaChaCore::<R20, _>::new_with_counter(&[0x42; 32].into(), &[0x24; 12].into(), 1);
+    /// let cipher = ChaCha20::from_core(core);
+    /// ```
+    pub fn new_with_counter(key: &Key, iv: &Nonce, counter: u32) -> Self {
+        let mut core = ChaChaCore::<R, Ietf>::new(key.as_ref(), iv.as_ref());
+        core.state[12] = counter;
+        core
+    }
+}
+
+/// Error returned by [`ChaChaCore::new_checked`] when the provided key is all zeros.
+#[cfg(feature = "subtle")]
+#[cfg_attr(docsrs, doc(cfg(feature = "subtle")))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WeakKeyError;
+
+#[cfg(feature = "subtle")]
+impl fmt::Display for WeakKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("weak key: all-zero key")
+    }
+}
+
+#[cfg(feature = "subtle")]
+impl core::error::Error for WeakKeyError {}
+
+/// Extract the core from a cipher, e.g. to inspect its state or to drive a
+/// custom backend. Use [`StreamCipherCoreWrapper::from_core`] to convert it
+/// back.
+///
+/// The wrapper buffers the unused keystream of a partially consumed block,
+/// which the core does not hold, so the conversion fails with
+/// [`PartialBlockError`] unless the cipher is at a block boundary.
+impl<R: Rounds, V: Variant> TryFrom<StreamCipherCoreWrapper<ChaChaCore<R, V>>>
+    for ChaChaCore<R, V>
+{
+    type Error = PartialBlockError;
+
+    fn try_from(cipher: StreamCipherCoreWrapper<ChaChaCore<R, V>>) -> Result<Self, Self::Error> {
+        if cipher.current_pos::<u128>() % 64 != 0 {
+            return Err(PartialBlockError);
+        }
+        Ok(cipher.get_core().clone())
+    }
+}
+
+/// Error returned when converting a cipher into its core in the middle of
+/// a block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PartialBlockError;
+
+impl fmt::Display for PartialBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("cipher is not at a block boundary")
+    }
+}
+
+impl core::error::Error for PartialBlockError {}
@@ -0,0 +1,104 @@
+//! ChaCha variant with the number of double rounds selected at runtime
+
+use cipher::{
+    consts::U64, BlockSizeUser, InOutBuf, OverflowError, SeekNum, StreamCipher,
+    StreamCipherClosure, StreamCipherCore, StreamCipherCoreWrapper, StreamCipherError,
+    StreamCipherSeek, StreamCipherSeekCore,
+};
+
+use crate::{
+    backends,
+    chacha::{Key, Nonce},
+    variants::Ietf,
+    ChaChaCore, R20,
+};
+
+/// ChaCha stream cipher (RFC 8439 version with 96-bit nonce) performing
+/// a number of double rounds selected at runtime.
+///
+/// This type is intended for research purposes, e.g. analyzing the security
+/// margin of reduced-round variants. It always uses the portable software
+/// backend, so it is significantly slower than [`ChaCha20`][crate::ChaCha20],
+/// [`ChaCha12`][crate::ChaCha12] and [`ChaCha8`][crate::ChaCha8], which should
+/// be used instead whenever the number of rounds is known at compile time.
+pub struct ChaCha20Dynamic(StreamCipherCoreWrapper<DynamicCore>);
+
+impl ChaCha20Dynamic {
+    /// Create new cipher instance from key, nonce, and number of double rounds.
+    ///
+    /// ChaCha20 performs 10 double rounds, ChaCha12 performs 6, and ChaCha8
+    /// performs 4.
+    pub fn new(key: &Key, iv: &Nonce, double_rounds: u8) -> Self {
+        Self(StreamCipherCoreWrapper::from_core(DynamicCore {
+            core: ChaChaCore::new(key.as_ref(), iv.as_ref()),
+            double_rounds,
+        }))
+    }
+
+    /// Get the number of double rounds performed by this cipher.
+    pub fn double_rounds(&self) -> u8 {
+        self.0.get_core().double_rounds
+    }
+}
+
+impl StreamCipher for ChaCha20Dynamic {
+    #[inline]
+    fn try_apply_keystream_inout(
+        &mut self,
+        buf: InOutBuf<'_, '_, u8>,
+    ) -> Result<(), StreamCipherError> {
+        self.0.try_apply_keystream_inout(buf)
+    }
+}
+
+impl StreamCipherSeek for ChaCha20Dynamic {
+    #[inline]
+    fn try_current_pos<T: SeekNum>(&self) -> Result<T, OverflowError> {
+        self.0.try_current_pos()
+    }
+
+    #[inline]
+    fn try_seek<T: SeekNum>(&mut self, pos: T) -> Result<(), StreamCipherError> {
+        self.0.try_seek(pos)
+    }
+}
+
+/// Core of [`ChaCha20Dynamic`].
+///
+/// The rounds parameter of the inner [`ChaChaCore`] is unused.
+struct DynamicCore {
+    core: ChaChaCore<R20, Ietf>,
+    double_rounds: u8,
+}
+
+impl BlockSizeUser for DynamicCore {
+    type BlockSize = U64;
+}
+
+impl StreamCipherCore for DynamicCore {
+    #[inline(always)]
+    fn remaining_blocks(&self) -> Option<usize> {
+        self.core.remaining_blocks()
+    }
+
+    fn process_with_backend(&mut self, f: impl StreamCipherClosure<BlockSize = Self::BlockSize>) {
+        f.call(&mut backends::soft::DynamicBackend {
+            state: &mut self.core.state,
+            double_rounds: self.double_rounds.into(),
+        });
+    }
+}
+
+impl StreamCipherSeekCore for DynamicCore {
+    type Counter = u32;
+
+    #[inline(always)]
+    fn get_block_pos(&self) -> u32 {
+        self.core.get_block_pos()
+    }
+
+    #[inline(always)]
+    fn set_block_pos(&mut self, pos: u32) {
+        self.core.set_block_pos(pos);
+    }
+}
@@ -17,6 +17,25 @@ use crate::variants::Legacy;
 /// **WARNING:** this implementation uses 32-bit counter, while the original
 /// implementation uses 64-bit counter. In other words, it does
 /// not allow encrypting of more than 256 GiB of data.
+///
+/// The block with counter `u32::MAX` is never produced, so the exact limit
+/// is 256 GiB minus 64 bytes. Applying keystream past it returns an error
+/// instead of wrapping the counter. The number of whole blocks left can be
+/// queried through the core, e.g. to rekey in time:
+///
+/// ```
+/// use chacha20::ChaCha20Legacy;
+/// use chacha20::cipher::{KeyIvInit, StreamCipher, StreamCipherCore, StreamCipherSeek};
+///
+/// let mut cipher = ChaCha20Legacy::new(&[0x42; 32].into(), &[0x24; 8].into());
+/// cipher.seek((u64::from(u32::MAX) - 1) * 64);
+/// assert_eq!(cipher.get_core().remaining_blocks(), Some(1));
+///
+/// let mut buf = [0u8; 65];
+/// assert!(cipher.try_apply_keystream(&mut buf[..64]).is_ok());
+/// assert_eq!(cipher.get_core().remaining_blocks(), Some(0));
+/// assert!(cipher.try_apply_keystream(&mut buf[64..]).is_err());
+/// ```
 pub type ChaCha20Legacy = StreamCipherCoreWrapper<ChaCha20LegacyCore>;
 
 /// /// The ChaCha20 stream cipher (legacy "djb" construction with 64-bit nonce).
@@ -12,6 +12,8 @@
 //! - [`ChaCha8`] / [`ChaCha12`]: reduced round variants of ChaCha20
 //! - [`XChaCha20`]: 192-bit extended nonce variant
 //! - [`XChaCha8`] / [`XChaCha12`]: reduced round variants of XChaCha20
+//! - [`ChaCha20Dynamic`]: variant with the number of rounds selected at runtime,
+//!   intended for research purposes (requires the `dynamic-rounds` feature)
 //! - [`ChaCha20Legacy`]: "djb" variant with 64-bit nonce.
 //! **WARNING:** This implementation internally uses 32-bit counter,
 //! while the original implementation uses 64-bit counter. In other words,
@@ -78,6 +80,12 @@
 //!     cipher.apply_keystream(chunk);
 //! }
 //! assert_eq!(buffer, ciphertext);
+//!
+//! // keystream can also be applied buffer-to-buffer, leaving the input intact
+//! cipher.seek(0u32);
+//! let mut output = [0u8; 16];
+//! cipher.apply_keystream_b2b(&plaintext, &mut output).unwrap();
+//! assert_eq!(output, ciphertext);
 //! ```
 //!
 //! # Configuration Flags
@@ -95,6 +103,29 @@
 //!
 //! You SHOULD NOT enable several `force` flags simultaneously.
 //!
+//! # Constant-time Properties
+//!
+//! ChaCha is built only from 32-bit additions, XORs and rotations by fixed
+//! amounts, so it needs no secret-indexed table lookups or secret-dependent
+//! branches. Every backend keeps these properties:
+//!
+//! - soft: the quarter round uses `wrapping_add`, `^` and `rotate_left` by
+//!   constants, and the number of rounds is fixed by the variant.
+//! - SSE2: the same operations on 128-bit vectors, with rotations done as
+//!   pairs of shifts by constant amounts.
+//! - AVX2: the same operations on 256-bit vectors. Rotations by 8 and 16 bits
+//!   use `vpshufb` with a constant shuffle mask.
+//! - NEON: the same operations on 128-bit vectors. Rotations by 8 and 16 bits
+//!   use `tbl` with a constant index vector.
+//!
+//! All branches taken while generating keystream depend only on public data:
+//! the detected CPU features, the block counter, and the length of the data
+//! and its position within the current block. The key, nonce and plaintext
+//! never influence control flow or memory addresses.
+//!
+//! These properties come from reading the source. They are not checked by a
+//! tool, and the compiler gives no guarantee that they survive optimization.
+//!
 //! [ChaCha]: https://tools.ietf.org/html/rfc8439
 //! [Salsa]: https://en.wikipedia.org/wiki/Salsa20
 //! [`chacha20poly1305`]: https://docs.rs/chacha20poly1305
@@ -120,30 +151,49 @@ use core::marker::PhantomData;
 #[cfg(feature = "zeroize")]
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
+#[cfg(feature = "dev-backend-switch")]
+mod backend_switch;
 mod backends;
 #[cfg(feature = "cipher")]
 mod chacha;
+#[cfg(feature = "dynamic-rounds")]
+mod dynamic;
 #[cfg(feature = "legacy")]
 mod legacy;
 #[cfg(feature = "rng")]
 mod rng;
+#[cfg(feature = "squeezer")]
+mod squeezer;
 #[cfg(feature = "xchacha")]
 mod xchacha;
 
 mod variants;
 use variants::Variant;
 
+#[cfg(all(feature = "cipher", feature = "subtle"))]
+pub use chacha::WeakKeyError;
 #[cfg(feature = "cipher")]
-pub use chacha::{ChaCha12, ChaCha20, ChaCha8, Key, KeyIvInit};
+pub use chacha::{ChaCha12, ChaCha20, ChaCha8, Key, KeyIvInit, PartialBlockError};
 #[cfg(feature = "rng")]
 pub use rand_core;
 #[cfg(feature = "rng")]
-pub use rng::{ChaCha12Core, ChaCha12Rng, ChaCha20Core, ChaCha20Rng, ChaCha8Core, ChaCha8Rng};
-
+pub use rng::{
+    ChaCha12Core, ChaCha12Rng, ChaCha20Core, ChaCha20Rng, ChaCha8Core, ChaCha8Rng, ChaChaSnapshot,
+};
+
+#[cfg(feature = "dev-backend-switch")]
+pub use backend_switch::ChaChaBackend;
+#[cfg(feature = "dynamic-rounds")]
+pub use dynamic::ChaCha20Dynamic;
 #[cfg(feature = "legacy")]
 pub use legacy::{ChaCha20Legacy, LegacyNonce};
+#[cfg(feature = "squeezer")]
+pub use squeezer::ChaChaSqueezer;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use stream_cipher_writer::StreamCipherWriter;
 #[cfg(feature = "xchacha")]
-pub use xchacha::{hchacha, XChaCha12, XChaCha20, XChaCha8, XNonce};
+pub use xchacha::{hchacha, XChaCha12, XChaCha20, XChaCha8, XChaChaCore, XNonce};
 
 /// State initialization constant ("expand 32-byte k")
 const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
@@ -208,7 +258,7 @@ cfg_if! {
 }
 
 /// The ChaCha core function.
-#[cfg_attr(feature = "rng", derive(Clone))]
+#[derive(Clone)]
 pub struct ChaChaCore<R: Rounds, V: Variant> {
     /// Internal state of the core function
     state: [u32; STATE_WORDS],
@@ -219,6 +269,9 @@ pub struct ChaChaCore<R: Rounds, V: Variant> {
     rounds: PhantomData<R>,
     /// the variant of the implementation
     variant: PhantomData<V>,
+    /// Backend forced at runtime, if any
+    #[cfg(feature = "dev-backend-switch")]
+    backend: Option<ChaChaBackend>,
 }
 
 impl<R: Rounds, V: Variant> ChaChaCore<R, V> {
@@ -259,6 +312,8 @@ impl<R: Rounds, V: Variant> ChaChaCore<R, V> {
             tokens,
             rounds: PhantomData,
             variant: PhantomData,
+            #[cfg(feature = "dev-backend-switch")]
+            backend: None,
         }
     }
 }
@@ -290,6 +345,11 @@ impl<R: Rounds, V: Variant> StreamCipherCore for ChaChaCore<R, V> {
         &mut self,
         f: impl cipher::StreamCipherClosure<BlockSize = Self::BlockSize>,
     ) {
+        #[cfg(feature = "dev-backend-switch")]
+        if let Some(backend) = self.backend {
+            return backend_switch::process_with(self, backend, f);
+        }
+
         cfg_if! {
             if #[cfg(chacha20_force_soft)] {
                 f.call(&mut backends::soft::Backend(self));
@@ -318,7 +378,7 @@ impl<R: Rounds, V: Variant> StreamCipherCore for ChaChaCore<R, V> {
                         }
                     }
                 }
-            } else if #[cfg(all(target_arch = "aarch64", target_feature = "neon"))] {
+            } else if #[cfg(all(target_arch = "aarch64", target_feature = "neon", target_endian = "little"))] {
                 unsafe {
                     backends::neon::inner::<R, _>(&mut self.state, f);
                 }
@@ -374,3 +434,75 @@ pub(crate) fn quarter_round(
     state[b] ^= state[c];
     state[b] = state[b].rotate_left(7);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{quarter_round, STATE_WORDS};
+
+    /// Quarter round test vector from RFC 8439 §2.1.1.
+    #[test]
+    fn quarter_round_rfc8439_2_1_1() {
+        let mut state = [0u32; STATE_WORDS];
+        state[..4].copy_from_slice(&[0x11111111, 0x01020304, 0x9b8d6f43, 0x01234567]);
+        quarter_round(0, 1, 2, 3, &mut state);
+        assert_eq!(state[..4], [0xea2a92f4, 0xcb1cf8ce, 0x4581472e, 0x5881c4bb]);
+    }
+
+    /// Quarter round on a ChaCha state from RFC 8439 §2.2.1.
+    #[test]
+    fn quarter_round_rfc8439_2_2_1() {
+        let mut state = [
+            0x879531e0, 0xc5ecf37d, 0x516461b1, 0xc9a62f8a, 0x44c20ef3, 0x3390af7f, 0xd9fc690b,
+            0x2a5f714c, 0x53372767, 0xb00a5631, 0x974c541a, 0x359e9963, 0x5c971061, 0x3d631689,
+            0x2098d9d6, 0x91dbd320,
+        ];
+        quarter_round(2, 7, 8, 13, &mut state);
+        assert_eq!(
+            state,
+            [
+                0x879531e0, 0xc5ecf37d, 0xbdb886dc, 0xc9a62f8a, 0x44c20ef3, 0x3390af7f, 0xd9fc690b,
+                0xcfacafd2, 0xe46bea80, 0xb00a5631, 0x974c541a, 0x359e9963, 0x5c971061, 0xccc07c79,
+                0x2098d9d6, 0x91dbd320,
+            ]
+        );
+    }
+
+    /// Compare the quarter round against a direct transcription of RFC 8439
+    /// §2.1 over many inputs, biased towards the edge values of the additions.
+    #[test]
+    fn quarter_round_matches_reference() {
+        fn reference([mut a, mut b, mut c, mut d]: [u32; 4]) -> [u32; 4] {
+            a = a.wrapping_add(b);
+            d ^= a;
+            d = d.rotate_left(16);
+            c = c.wrapping_add(d);
+            b ^= c;
+            b = b.rotate_left(12);
+            a = a.wrapping_add(b);
+            d ^= a;
+            d = d.rotate_left(8);
+            c = c.wrapping_add(d);
+            b ^= c;
+            b = b.rotate_left(7);
+            [a, b, c, d]
+        }
+
+        let edges = [0, 1, 0x8000_0000, 0x7fff_ffff, u32::MAX];
+        let mut x = 0x9e37_79b9u32;
+        for i in 0..1024 {
+            let mut input = [0u32; 4];
+            for word in input.iter_mut() {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                *word = if i < 625 { edges[(x % 5) as usize] } else { x };
+            }
+
+            let mut state = [0u32; STATE_WORDS];
+            state[..4].copy_from_slice(&input);
+            quarter_round(0, 1, 2, 3, &mut state);
+            assert_eq!(state[..4], reference(input));
+            assert!(state[4..].iter().all(|&w| w == 0));
+        }
+    }
+}
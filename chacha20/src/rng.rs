@@ -10,7 +10,7 @@ use core::fmt::Debug;
 
 use rand_core::{
     block::{BlockRng, BlockRngCore, CryptoBlockRng},
-    impl_try_rng_from_rng_core, CryptoRng, RngCore, SeedableRng,
+    impl_try_crypto_rng_from_crypto_rng, CryptoRng, RngCore, SeedableRng,
 };
 
 #[cfg(feature = "serde1")]
@@ -70,9 +70,42 @@ impl Drop for Seed {
 #[cfg(feature = "zeroize")]
 impl ZeroizeOnDrop for Seed {}
 
+// Custom Debug implementation that does not expose the seed
 impl Debug for Seed {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        self.0.fmt(f)
+        f.write_str("Seed { .. }")
+    }
+}
+
+/// A saved position of a ChaCha RNG, as returned by `snapshot()` and
+/// accepted by `restore()`.
+///
+/// It holds the seed, the stream and the word position, which is all that
+/// determines the future output of the RNG. The seed is zeroized on drop when
+/// the zeroize feature is enabled, and it is not printed by `Debug`.
+#[derive(PartialEq, Eq)]
+pub struct ChaChaSnapshot {
+    pub(crate) seed: Seed,
+    pub(crate) stream: u128,
+    pub(crate) word_pos: u64,
+}
+
+impl Clone for ChaChaSnapshot {
+    fn clone(&self) -> Self {
+        Self {
+            seed: self.seed.0.into(),
+            stream: self.stream,
+            word_pos: self.word_pos,
+        }
+    }
+}
+
+impl Debug for ChaChaSnapshot {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ChaChaSnapshot")
+            .field("stream", &self.stream)
+            .field("word_pos", &self.word_pos)
+            .finish_non_exhaustive()
     }
 }
 
@@ -181,16 +214,34 @@ impl Drop for BlockRngResults {
     }
 }
 
+#[cfg(not(feature = "rng-8-blocks"))]
 const BUFFER_SIZE: usize = 64;
+#[cfg(feature = "rng-8-blocks")]
+const BUFFER_SIZE: usize = 128;
+
+// number of 32-bit words generated by a single backend call
+const PAR_WORDS: usize = 64;
 
-// NB. this must remain consistent with some currently hard-coded numbers in this module
 const BUF_BLOCKS: u8 = BUFFER_SIZE as u8 >> 4;
 
 impl<R: Rounds, V: Variant> ChaChaCore<R, V> {
+    /// Fills the RNG buffer, 4 blocks per backend call
+    #[cfg(feature = "rand_core")]
+    fn generate(&mut self, buffer: &mut [u32; BUFFER_SIZE]) {
+        for chunk in buffer.chunks_exact_mut(PAR_WORDS) {
+            self.generate_par(chunk.try_into().unwrap());
+        }
+    }
+
     /// Generates 4 blocks in parallel with avx2 & neon, but merely fills
     /// 4 blocks with sse2 & soft
     #[cfg(feature = "rand_core")]
-    fn generate(&mut self, buffer: &mut [u32; 64]) {
+    fn generate_par(&mut self, buffer: &mut [u32; PAR_WORDS]) {
+        #[cfg(feature = "dev-backend-switch")]
+        if let Some(backend) = self.backend {
+            return crate::backend_switch::generate_par_with(self, backend, buffer);
+        }
+
         cfg_if! {
             if #[cfg(chacha20_force_soft)] {
                 backends::soft::Backend(self).gen_ks_blocks(buffer);
@@ -219,7 +270,7 @@ impl<R: Rounds, V: Variant> ChaChaCore<R, V> {
                         }
                     }
                 }
-            } else if #[cfg(all(target_arch = "aarch64", target_feature = "neon"))] {
+            } else if #[cfg(all(target_arch = "aarch64", target_feature = "neon", target_endian = "little"))] {
                 unsafe {
                     backends::neon::rng_inner::<R, V>(self, buffer);
                 }
@@ -356,7 +407,7 @@ macro_rules! impl_chacha_rng {
         // Custom Debug implementation that does not expose the internal state
         impl Debug for $ChaChaXRng {
             fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-                write!(f, "ChaChaXCore {{}}")
+                f.write_str(concat!(stringify!($ChaChaXRng), " { .. }"))
             }
         }
 
@@ -384,10 +435,34 @@ macro_rules! impl_chacha_rng {
             }
         }
 
-        impl_try_rng_from_rng_core!($ChaChaXRng);
+        impl_try_crypto_rng_from_crypto_rng!($ChaChaXRng);
 
         impl $ChaChaXRng {
-            // The buffer is a 4-block window, i.e. it is always at a block-aligned position in the
+            /// Create a new RNG from `seed` which outputs the stream selected by `stream`.
+            ///
+            /// This is equivalent to calling [`SeedableRng::from_seed`] followed by
+            /// [`set_stream`][Self::set_stream], and takes the same argument types as
+            /// the latter.
+            #[inline]
+            pub fn from_seed_with_stream<S: Into<StreamId>>(seed: [u8; 32], stream: S) -> Self {
+                let mut rng = Self::from_seed(seed);
+                rng.set_stream(stream);
+                rng
+            }
+
+            /// Create a new RNG from `seed` positioned at `word_pos`.
+            ///
+            /// This is equivalent to calling [`SeedableRng::from_seed`] followed by
+            /// [`set_word_pos`][Self::set_word_pos], and takes the same argument types
+            /// as the latter.
+            #[inline]
+            pub fn from_seed_at<W: Into<WordPosInput>>(seed: [u8; 32], word_pos: W) -> Self {
+                let mut rng = Self::from_seed(seed);
+                rng.set_word_pos(word_pos);
+                rng
+            }
+
+            // The buffer is a `BUF_BLOCKS`-block window, i.e. it is always at a block-aligned position in the
             // stream but if the stream has been sought it may not be self-aligned.
 
             /// Get the offset from the start of the stream, in 32-bit words.
@@ -420,7 +495,7 @@ macro_rules! impl_chacha_rng {
             pub fn set_word_pos<W: Into<WordPosInput>>(&mut self, word_offset: W) {
                 let word_pos: WordPosInput = word_offset.into();
                 self.core.core.0.state[12] = word_pos.block_pos;
-                // generate will increase block_pos by 4
+                // generate will increase block_pos by BUF_BLOCKS
                 self.core.generate_and_set(word_pos.index);
             }
 
@@ -483,6 +558,19 @@ macro_rules! impl_chacha_rng {
                 u128::from_le_bytes(result)
             }
 
+            /// Get the stream number as the 96-bit RFC 8439 nonce it corresponds to.
+            #[inline]
+            pub fn get_nonce(&self) -> [u8; 12] {
+                let mut result = [0u8; 12];
+                for (chunk, &big) in result
+                    .chunks_exact_mut(4)
+                    .zip(self.core.core.0.state[Ietf::NONCE_INDEX..BLOCK_WORDS as usize].iter())
+                {
+                    chunk.copy_from_slice(&big.to_le_bytes());
+                }
+                result
+            }
+
             /// Get the seed.
             #[inline]
             pub fn get_seed(&self) -> [u8; 32] {
@@ -496,6 +584,78 @@ macro_rules! impl_chacha_rng {
                 }
                 result
             }
+
+            /// Fill `out` with whole keystream blocks, starting at the current position.
+            ///
+            /// This produces the same words as calling [`RngCore::next_u32`]
+            /// `16 * out.len()` times, and advances the word position by as much, but
+            /// writes the blocks directly instead of going through the RNG buffer.
+            ///
+            /// # Panics
+            ///
+            /// If the word position is not at a block boundary, i.e. not a multiple of 16.
+            pub fn fill_blocks(&mut self, out: &mut [[u32; 16]]) {
+                let word_pos = self.get_word_pos();
+                assert_eq!(word_pos % 16, 0, "RNG is not at a block boundary");
+                let len = out.len() as u64;
+
+                let core = &mut self.core.core.0;
+                core.state[12] = (word_pos >> 4) as u32;
+                let mut buf = [0u32; PAR_WORDS];
+                let mut chunks = out.chunks_exact_mut(PAR_WORDS / 16);
+                for chunk in &mut chunks {
+                    core.generate_par(&mut buf);
+                    for (block, words) in chunk.iter_mut().zip(buf.chunks_exact(16)) {
+                        block.copy_from_slice(words);
+                    }
+                }
+                let rem = chunks.into_remainder();
+                if !rem.is_empty() {
+                    core.generate_par(&mut buf);
+                    for (block, words) in rem.iter_mut().zip(buf.chunks_exact(16)) {
+                        block.copy_from_slice(words);
+                    }
+                }
+                #[cfg(feature = "zeroize")]
+                buf.zeroize();
+
+                self.set_word_pos(word_pos.wrapping_add(16 * len));
+            }
+        }
+
+        #[cfg(feature = "dev-backend-switch")]
+        impl $ChaChaXRng {
+            /// Force the RNG to use `backend` instead of the automatically selected one.
+            ///
+            /// Words which are already buffered are not regenerated.
+            ///
+            /// # Panics
+            ///
+            /// If `backend` is not [available][crate::ChaChaBackend::is_available].
+            #[cfg_attr(docsrs, doc(cfg(feature = "dev-backend-switch")))]
+            pub fn with_backend(mut self, backend: crate::ChaChaBackend) -> Self {
+                self.core.core.0.set_backend(backend);
+                self
+            }
+        }
+
+        impl $ChaChaXRng {
+            /// Save the current position of the RNG.
+            ///
+            /// Passing the snapshot to [`Self::restore`] later rewinds (or
+            /// fast-forwards) the RNG back to this point.
+            #[inline]
+            pub fn snapshot(&self) -> ChaChaSnapshot {
+                $abst::$ChaChaXRng::from(self).into()
+            }
+
+            /// Return the RNG to the position saved in `snapshot`.
+            ///
+            /// This replaces the seed and stream as well as the word position.
+            #[inline]
+            pub fn restore(&mut self, snapshot: &ChaChaSnapshot) {
+                *self = Self::from(&$abst::$ChaChaXRng::from(snapshot));
+            }
         }
 
         impl PartialEq<$ChaChaXRng> for $ChaChaXRng {
@@ -562,6 +722,26 @@ macro_rules! impl_chacha_rng {
                 }
             }
 
+            impl From<$ChaChaXRng> for crate::rng::ChaChaSnapshot {
+                fn from(a: $ChaChaXRng) -> Self {
+                    Self {
+                        seed: a.seed.0.into(),
+                        stream: a.stream,
+                        word_pos: a.word_pos,
+                    }
+                }
+            }
+
+            impl From<&crate::rng::ChaChaSnapshot> for $ChaChaXRng {
+                fn from(s: &crate::rng::ChaChaSnapshot) -> Self {
+                    Self {
+                        seed: s.seed.0.into(),
+                        stream: s.stream,
+                        word_pos: s.word_pos,
+                    }
+                }
+            }
+
             impl From<&$ChaChaXRng> for super::$ChaChaXRng {
                 // Construct one of the possible concrete RNGs realizing an abstract state.
                 fn from(a: &$ChaChaXRng) -> Self {
@@ -928,6 +1108,94 @@ pub(crate) mod tests {
         assert_eq!(results, expected);
     }
 
+    #[test]
+    fn test_chacha_from_seed_with_stream() {
+        let seed = [7u8; 32];
+        let mut rng = ChaChaRng::from_seed_with_stream(seed, 42);
+        assert_eq!(rng.get_seed(), seed);
+        assert_eq!(rng.get_stream(), 42);
+
+        let mut expected = ChaChaRng::from_seed(seed);
+        expected.set_stream(42);
+        assert_eq!(rng, expected);
+
+        let mut other =
+            ChaChaRng::from_seed_with_stream(seed, [43u8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let mut buf1 = [0u8; 1024];
+        let mut buf2 = [0u8; 1024];
+        rng.fill_bytes(&mut buf1);
+        other.fill_bytes(&mut buf2);
+        for block1 in buf1.chunks_exact(64) {
+            for block2 in buf2.chunks_exact(64) {
+                assert_ne!(block1, block2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_chacha_from_seed_at() {
+        let seed = [7u8; 32];
+        for word_pos in [0u64, 1, 15, 16, 17, 1000, 0xf_ffff_ffff] {
+            let mut rng = ChaChaRng::from_seed_at(seed, word_pos);
+            assert_eq!(rng.get_word_pos(), word_pos);
+
+            let mut expected = ChaChaRng::from_seed(seed);
+            expected.set_word_pos(word_pos);
+            assert_eq!(rng, expected);
+            assert_eq!(rng.next_u32(), expected.next_u32());
+        }
+
+        let rng = ChaChaRng::from_seed_at(seed, [0x10u8, 0, 0, 0, 3]);
+        assert_eq!(rng.get_word_pos(), 0x103);
+    }
+
+    #[test]
+    fn test_chacha_fill_blocks() {
+        let seed = [7u8; 32];
+        for len in [0, 1, 3, 4, 5, 9] {
+            for start in [0u64, 16, 64, 0xf_ffff_ffe0] {
+                let mut rng = ChaChaRng::from_seed_at(seed, start);
+                let mut blocks = [[0u32; 16]; 9];
+                rng.fill_blocks(&mut blocks[..len]);
+                assert_eq!(
+                    rng.get_word_pos(),
+                    (start + 16 * len as u64) & 0xf_ffff_ffff
+                );
+
+                let mut expected = ChaChaRng::from_seed_at(seed, start);
+                for block in &blocks[..len] {
+                    for &word in block {
+                        assert_eq!(word, expected.next_u32());
+                    }
+                }
+                assert_eq!(rng.next_u32(), expected.next_u32());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_chacha_fill_blocks_unaligned() {
+        let mut rng = ChaChaRng::from_seed([7u8; 32]);
+        rng.next_u32();
+        rng.fill_blocks(&mut [[0u32; 16]; 1]);
+    }
+
+    #[test]
+    fn test_chacha_get_nonce() {
+        use hex_literal::hex;
+        let nonce = hex!("000000090000004a00000000");
+        let rng = ChaChaRng::from_seed_with_stream([0u8; 32], nonce);
+        assert_eq!(rng.get_nonce(), nonce);
+        assert_eq!(
+            rng.get_stream(),
+            u128::from_le_bytes(hex!("000000090000004a0000000000000000"))
+        );
+
+        let restored = ChaChaRng::from_seed_with_stream(rng.get_seed(), rng.get_nonce());
+        assert_eq!(rng, restored);
+    }
+
     #[test]
     fn test_chacha_clone_streams() {
         let seed = [
@@ -977,11 +1245,24 @@ pub(crate) mod tests {
         assert_eq!(rng.get_word_pos(), last_block);
     }
 
+    #[test]
+    fn test_chacha_buffer_refills() {
+        let mut rng = ChaChaRng::from_seed([3u8; 32]);
+        // read across several refills of the buffer, whatever its size, and
+        // check every word against a fresh RNG sought to its position
+        for word_pos in 0..3 * BUFFER_SIZE as u64 + 5 {
+            let mut expected = ChaChaRng::from_seed([3u8; 32]);
+            expected.set_word_pos(word_pos);
+            assert_eq!(rng.get_word_pos(), word_pos);
+            assert_eq!(rng.next_u32(), expected.next_u32());
+        }
+    }
+
     #[test]
     fn test_chacha_word_pos_zero() {
         let mut rng = ChaChaRng::from_seed(Default::default());
         assert_eq!(rng.core.core.0.state[12], 0);
-        assert_eq!(rng.core.index(), 64);
+        assert_eq!(rng.core.index(), BUFFER_SIZE);
         assert_eq!(rng.get_word_pos(), 0);
         rng.set_word_pos(0);
         assert_eq!(rng.get_word_pos(), 0);
@@ -1088,15 +1369,64 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn test_chacha_snapshot_restore() {
+        let mut rng = ChaChaRng::from_seed_with_stream([7u8; 32], 3);
+        rng.next_u32();
+        let snapshot = rng.snapshot();
+
+        let mut expected = [0u8; 200];
+        rng.fill_bytes(&mut expected);
+
+        // a clone of the snapshot must still be usable after the original is gone
+        let saved = snapshot.clone();
+        drop(snapshot);
+        rng.set_stream(9);
+        rng.next_u64();
+
+        rng.restore(&saved);
+        assert_eq!(rng.snapshot(), saved);
+        let mut actual = [0u8; 200];
+        rng.fill_bytes(&mut actual);
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     #[allow(trivial_casts)]
     fn test_trait_objects() {
-        use rand_core::CryptoRng;
+        use core::convert::Infallible;
+        use rand_core::{CryptoRng, TryCryptoRng, TryRngCore};
 
         let mut rng1 = ChaChaRng::from_seed(Default::default());
         let rng2 = &mut rng1.clone() as &mut dyn CryptoRng;
         for _ in 0..1000 {
             assert_eq!(rng1.next_u64(), rng2.next_u64());
         }
+
+        let mut clone = rng1.clone();
+        let rng2 = &mut clone as &mut dyn RngCore;
+        assert_eq!(rng1.next_u32(), rng2.next_u32());
+
+        let mut clone = rng1.clone();
+        let rng2 = &mut clone as &mut dyn TryRngCore<Error = Infallible>;
+        assert_eq!(Ok(rng1.next_u64()), rng2.try_next_u64());
+
+        let mut clone = rng1.clone();
+        let rng2 = &mut clone as &mut dyn TryCryptoRng<Error = Infallible>;
+        let (mut expected, mut actual) = ([0u8; 100], [0u8; 100]);
+        rng1.fill_bytes(&mut expected);
+        assert_eq!(rng2.try_fill_bytes(&mut actual), Ok(()));
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_crypto_rng_traits() {
+        use rand_core::TryCryptoRng;
+
+        fn assert_crypto_rng<R: CryptoRng + TryCryptoRng + SeedableRng>() {}
+
+        assert_crypto_rng::<ChaCha8Rng>();
+        assert_crypto_rng::<ChaCha12Rng>();
+        assert_crypto_rng::<ChaCha20Rng>();
     }
 }
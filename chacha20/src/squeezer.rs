@@ -0,0 +1,79 @@
+//! Keystream squeezer with domain separation by stream id.
+
+use crate::{chacha::Nonce, variants::Ietf, ChaCha20, ChaChaCore, Key, R20};
+use cipher::{KeyIvInit, StreamCipher};
+
+/// Squeezes ChaCha20 keystream for PRF-like use, with domain separation
+/// through the nonce.
+///
+/// The squeezer is keyed once and then outputs the ChaCha20 keystream of the
+/// current domain. [`next_domain`][Self::next_domain] switches to another
+/// domain by setting the nonce to the given label and restarting the block
+/// counter, so the outputs of distinct labels are independent keystreams.
+/// Domain `0` is selected initially.
+///
+/// This is an ad-hoc construction on top of ChaCha20, not a standardized KDF
+/// or PRF. Prefer a standardized construction where interoperability matters.
+///
+/// # Example
+///
+/// ```
+/// use chacha20::ChaChaSqueezer;
+///
+/// let mut squeezer = ChaChaSqueezer::new(&[0x42; 32].into());
+/// squeezer.next_domain(1);
+/// let mut encryption_key = [0u8; 32];
+/// squeezer.squeeze(&mut encryption_key);
+///
+/// squeezer.next_domain(2);
+/// let mut mac_key = [0u8; 32];
+/// squeezer.squeeze(&mut mac_key);
+/// assert_ne!(encryption_key, mac_key);
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "squeezer")))]
+#[derive(Clone)]
+pub struct ChaChaSqueezer {
+    cipher: ChaCha20,
+}
+
+impl ChaChaSqueezer {
+    /// Create a new squeezer from `key`, starting in domain `0`.
+    pub fn new(key: &Key) -> Self {
+        Self {
+            cipher: ChaCha20::new(key, &Self::nonce(0)),
+        }
+    }
+
+    /// Fill `out` with the next bytes of the current domain's keystream.
+    ///
+    /// Consecutive calls continue where the previous one stopped.
+    ///
+    /// # Panics
+    ///
+    /// If the 256 GiB keystream of the current domain is exhausted.
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        out.fill(0);
+        self.cipher.apply_keystream(out);
+    }
+
+    /// Switch to the domain `label` and restart its keystream from the
+    /// beginning.
+    ///
+    /// The label is stored little-endian in the last 8 bytes of the nonce.
+    /// Selecting a label again reproduces the same output.
+    pub fn next_domain(&mut self, label: u64) {
+        let mut core: ChaChaCore<R20, Ietf> = self.cipher.get_core().clone();
+        let nonce = Self::nonce(label);
+        for (word, chunk) in core.state[13..].iter_mut().zip(nonce.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+        core.state[12] = 0;
+        self.cipher = ChaCha20::from_core(core);
+    }
+
+    fn nonce(label: u64) -> Nonce {
+        let mut nonce = Nonce::default();
+        nonce[4..].copy_from_slice(&label.to_le_bytes());
+        nonce
+    }
+}
@@ -42,6 +42,7 @@ pub type XChaCha12 = StreamCipherCoreWrapper<XChaChaCore<R12>>;
 pub type XChaCha8 = StreamCipherCoreWrapper<XChaChaCore<R8>>;
 
 /// The XChaCha core function.
+#[derive(Clone)]
 pub struct XChaChaCore<R: Rounds>(ChaChaCore<R, Ietf>);
 
 impl<R: Rounds> KeySizeUser for XChaChaCore<R> {
@@ -59,11 +60,23 @@ impl<R: Rounds> BlockSizeUser for XChaChaCore<R> {
 impl<R: Rounds> KeyIvInit for XChaChaCore<R> {
     fn new(key: &Key, iv: &XNonce) -> Self {
         let subkey = hchacha::<R>(key, iv[..16].as_ref().try_into().unwrap());
+        Self::with_subkey(&subkey, iv[16..].try_into().unwrap())
+    }
+}
 
+impl<R: Rounds> XChaChaCore<R> {
+    /// Create a core from a subkey computed by [`hchacha`] over the first 16
+    /// bytes of the nonce, and the last 8 bytes of the nonce.
+    ///
+    /// The subkey only depends on the key and the nonce prefix, so messages
+    /// whose nonces share a prefix can reuse it instead of computing HChaCha
+    /// for every message. The result is the same as [`KeyIvInit::new`] with
+    /// the full nonce.
+    pub fn with_subkey(subkey: &Key, nonce_suffix: &[u8; 8]) -> Self {
         let mut nonce = [0u8; 12];
         // first 4 bytes are 0, last 8 bytes are last 8 from the iv
         // according to draft-arciszewski-xchacha-03
-        nonce[4..].copy_from_slice(&iv[16..]);
+        nonce[4..].copy_from_slice(nonce_suffix);
         Self(ChaChaCore::<R, Ietf>::new(subkey.as_ref(), &nonce))
     }
 }
@@ -0,0 +1,9 @@
+//! Property tests checking that encrypting in arbitrary chunks matches
+//! encrypting in a single call.
+
+#![cfg(feature = "cipher")]
+
+use chacha20::ChaCha20;
+use stream_ciphers_test_helpers::chunked_test;
+
+chunked_test!(chacha20_chunked, ChaCha20, key: 32, iv: 12);
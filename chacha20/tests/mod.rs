@@ -20,8 +20,8 @@ cipher::stream_cipher_seek_test!(chacha20legacy_seek, ChaCha20Legacy);
 
 #[cfg(feature = "cipher")]
 mod chacha20test {
-    use chacha20::{ChaCha20, KeyIvInit};
-    use cipher::StreamCipher;
+    use chacha20::{ChaCha20, ChaChaCore, KeyIvInit, R20};
+    use cipher::{StreamCipher, StreamCipherSeek};
     use hex_literal::hex;
 
     //
@@ -94,6 +94,176 @@ mod chacha20test {
         cipher.apply_keystream(&mut buf);
         assert_eq!(&buf[..], &CIPHERTEXT[..]);
     }
+
+    #[cfg(feature = "subtle")]
+    #[test]
+    fn chacha20_new_checked() {
+        use chacha20::WeakKeyError;
+
+        assert_eq!(
+            ChaChaCore::<R20, _>::new_checked(&[0u8; 32].into(), &IV.into()).err(),
+            Some(WeakKeyError)
+        );
+
+        let mut key = [0u8; 32];
+        key[31] = 1;
+        assert!(ChaChaCore::<R20, _>::new_checked(&key.into(), &IV.into()).is_ok());
+
+        let core = ChaChaCore::new_checked(&KEY.into(), &IV.into()).unwrap();
+        let mut cipher = ChaCha20::from_core(core);
+        let mut prefix = [0u8; 64];
+        cipher.apply_keystream(&mut prefix);
+        let mut buf = PLAINTEXT;
+        cipher.apply_keystream(&mut buf);
+        assert_eq!(&buf[..], &CIPHERTEXT[..]);
+    }
+
+    #[test]
+    fn chacha20_rewind() {
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+        let mut prefix = [0u8; 64];
+        cipher.apply_keystream(&mut prefix);
+
+        let mut buf = PLAINTEXT;
+        cipher.apply_keystream(&mut buf);
+        assert_eq!(&buf[..], &CIPHERTEXT[..]);
+
+        cipher.seek(0u32);
+        cipher.apply_keystream(&mut prefix);
+        assert_eq!(prefix, [0u8; 64]);
+        cipher.apply_keystream(&mut buf);
+        assert_eq!(&buf[..], &PLAINTEXT[..]);
+    }
+
+    #[test]
+    fn chacha20_clone() {
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+        let mut prefix = [0u8; 100];
+        cipher.apply_keystream(&mut prefix);
+
+        let mut clone = cipher.clone();
+        let mut buf1 = [0u8; 300];
+        let mut buf2 = [0u8; 300];
+        cipher.apply_keystream(&mut buf1);
+        clone.apply_keystream(&mut buf2);
+        assert_eq!(buf1, buf2);
+    }
+
+    #[test]
+    fn chacha20_counter_overflow() {
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+
+        // the last usable block is `u32::MAX - 1`
+        cipher.seek(u64::from(u32::MAX - 1) * 64);
+        let mut buf = [0u8; 64];
+        assert!(cipher.try_apply_keystream(&mut buf).is_ok());
+
+        // the following block would reuse keystream after wrapping the counter
+        let mut buf = [0u8; 1];
+        assert!(cipher.try_apply_keystream(&mut buf).is_err());
+        assert_eq!(buf, [0u8; 1]);
+
+        cipher.seek(u64::from(u32::MAX) * 64);
+        assert!(cipher.try_apply_keystream(&mut buf).is_err());
+    }
+
+    #[test]
+    fn chacha20_byte_at_a_time() {
+        let mut expected = [0u8; 200];
+        ChaCha20::new(&KEY.into(), &IV.into()).apply_keystream(&mut expected);
+
+        // single-byte calls must keep the partial-block position across
+        // several block boundaries
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+        let mut buf = [0u8; 200];
+        for byte in buf.chunks_mut(1) {
+            cipher.try_apply_keystream(byte).unwrap();
+        }
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn chacha20_new_with_counter() {
+        for counter in [0, 1, 7, u32::MAX - 1] {
+            let mut expected = ChaCha20::new(&KEY.into(), &IV.into());
+            expected.seek(u64::from(counter) * 64);
+            let mut expected_buf = [0u8; 64];
+            expected.apply_keystream(&mut expected_buf);
+
+            let core = ChaChaCore::<R20, _>::new_with_counter(&KEY.into(), &IV.into(), counter);
+            let mut cipher = ChaCha20::from_core(core);
+            let mut buf = [0u8; 64];
+            cipher.apply_keystream(&mut buf);
+            assert_eq!(buf, expected_buf, "counter {counter}");
+        }
+    }
+
+    #[cfg(feature = "dev-backend-switch")]
+    #[test]
+    fn chacha20_all_backends() {
+        use chacha20::ChaChaBackend;
+
+        assert!(ChaChaBackend::Soft.is_available());
+
+        let mut expected = [0u8; 4096];
+        ChaCha20::new(&KEY.into(), &IV.into()).apply_keystream(&mut expected);
+
+        for backend in ChaChaBackend::ALL.into_iter().filter(|b| b.is_available()) {
+            let core = ChaChaCore::<R20, _>::new(&KEY.into(), &IV.into()).with_backend(backend);
+            let mut cipher = ChaCha20::from_core(core);
+
+            cipher.seek(64u32);
+            let mut buf = [0u8; 114];
+            cipher.apply_keystream(&mut buf);
+            assert_eq!(buf, KEYSTREAM, "{backend:?}");
+
+            cipher.seek(0u32);
+            let mut buf = [0u8; 4096];
+            for chunk in buf.chunks_mut(333) {
+                cipher.apply_keystream(chunk);
+            }
+            assert_eq!(buf, expected, "{backend:?}");
+        }
+    }
+
+    #[test]
+    fn chacha20_core_round_trip() {
+        use chacha20::PartialBlockError;
+
+        let mut expected = [0u8; 256];
+        ChaCha20::new(&KEY.into(), &IV.into()).apply_keystream(&mut expected);
+
+        let mut buf = [0u8; 256];
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+        cipher.apply_keystream(&mut buf[..100]);
+        assert_eq!(
+            ChaChaCore::try_from(cipher.clone()).err(),
+            Some(PartialBlockError)
+        );
+
+        cipher.apply_keystream(&mut buf[100..128]);
+        let core = ChaChaCore::<R20, _>::try_from(cipher).unwrap();
+        let mut cipher = ChaCha20::from_core(core);
+        cipher.apply_keystream(&mut buf[128..]);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn chacha20_b2b() {
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+        let mut expected = PLAINTEXT;
+        cipher.apply_keystream(&mut expected);
+
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+        let mut output = [0u8; 114];
+        cipher.apply_keystream_b2b(&PLAINTEXT, &mut output).unwrap();
+        assert_eq!(&output[..], &expected[..]);
+
+        // input and output buffers must have equal lengths
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+        let mut output = [0u8; 113];
+        assert!(cipher.apply_keystream_b2b(&PLAINTEXT, &mut output).is_err());
+    }
 }
 
 #[rustfmt::skip]
@@ -182,6 +352,67 @@ mod xchacha20 {
         cipher.apply_keystream(&mut buf);
         assert_eq!(&buf[..], &CIPHERTEXT[..]);
     }
+
+    #[test]
+    fn xchacha20_with_subkey() {
+        use chacha20::{hchacha, XChaChaCore, R20};
+
+        let subkey = hchacha::<R20>(&Key::from(KEY), IV[..16].try_into().unwrap());
+        let core = XChaChaCore::<R20>::with_subkey(&subkey, IV[16..].try_into().unwrap());
+        let mut cipher = XChaCha20::from_core(core);
+
+        let mut prefix = [0u8; 64];
+        cipher.apply_keystream(&mut prefix);
+
+        let mut buf = [0u8; 304];
+        cipher.apply_keystream(&mut buf);
+        assert_eq!(&buf[..], &KEYSTREAM[..]);
+    }
+}
+
+#[cfg(feature = "dynamic-rounds")]
+mod dynamic {
+    use chacha20::{ChaCha12, ChaCha20, ChaCha20Dynamic, ChaCha8, KeyIvInit};
+    use cipher::{StreamCipher, StreamCipherSeek};
+
+    const KEY: [u8; 32] = [0x42; 32];
+    const IV: [u8; 12] = [0x24; 12];
+
+    #[test]
+    fn chacha20_dynamic_matches_fixed() {
+        let mut expected = [0u8; 1000];
+        ChaCha20::new(&KEY.into(), &IV.into()).apply_keystream(&mut expected);
+        let mut buf = [0u8; 1000];
+        ChaCha20Dynamic::new(&KEY.into(), &IV.into(), 10).apply_keystream(&mut buf);
+        assert_eq!(buf, expected);
+
+        let mut expected = [0u8; 1000];
+        ChaCha12::new(&KEY.into(), &IV.into()).apply_keystream(&mut expected);
+        let mut buf = [0u8; 1000];
+        ChaCha20Dynamic::new(&KEY.into(), &IV.into(), 6).apply_keystream(&mut buf);
+        assert_eq!(buf, expected);
+
+        let mut expected = [0u8; 1000];
+        ChaCha8::new(&KEY.into(), &IV.into()).apply_keystream(&mut expected);
+        let mut buf = [0u8; 1000];
+        ChaCha20Dynamic::new(&KEY.into(), &IV.into(), 4).apply_keystream(&mut buf);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn chacha20_dynamic_seek() {
+        let mut cipher = ChaCha20::new(&KEY.into(), &IV.into());
+        let mut dynamic = ChaCha20Dynamic::new(&KEY.into(), &IV.into(), 10);
+        cipher.seek(1234u32);
+        dynamic.seek(1234u32);
+        assert_eq!(dynamic.current_pos::<u32>(), 1234);
+
+        let mut expected = [0u8; 100];
+        cipher.apply_keystream(&mut expected);
+        let mut buf = [0u8; 100];
+        dynamic.apply_keystream(&mut buf);
+        assert_eq!(buf, expected);
+    }
 }
 
 // Legacy "djb" version of ChaCha20 (64-bit nonce)
@@ -233,4 +464,194 @@ mod legacy {
             }
         }
     }
+
+    #[test]
+    fn chacha20_legacy_remaining_blocks() {
+        use cipher::StreamCipherCore;
+
+        let mut cipher = ChaCha20Legacy::new(&KEY_LONG.into(), &LegacyNonce::from(IV_LONG));
+        assert_eq!(cipher.get_core().remaining_blocks(), Some(u32::MAX as usize));
+
+        // the block with counter `u32::MAX` is never produced
+        let end = u64::from(u32::MAX) * 64;
+        cipher.seek(end - 3 * 64);
+        assert_eq!(cipher.get_core().remaining_blocks(), Some(3));
+
+        let mut buf = [0u8; 3 * 64];
+        cipher.apply_keystream(&mut buf[..100]);
+        cipher.apply_keystream(&mut buf[100..]);
+        assert_eq!(cipher.get_core().remaining_blocks(), Some(0));
+        assert_eq!(cipher.current_pos::<u64>(), end);
+
+        assert!(cipher.try_apply_keystream(&mut [0u8; 1]).is_err());
+        assert_eq!(cipher.current_pos::<u64>(), end);
+        assert!(cipher.try_seek(1u64 << 38).is_err());
+    }
+
+    #[test]
+    fn chacha20_legacy_new_from_slices() {
+        let mut cipher = ChaCha20Legacy::new_from_slices(&KEY_LONG, &IV_LONG).unwrap();
+        let mut buf = [0; 256];
+        cipher.apply_keystream(&mut buf);
+        assert_eq!(buf, EXPECTED_LONG);
+
+        assert!(ChaCha20Legacy::new_from_slices(&KEY_LONG, &IV_LONG[..7]).is_err());
+        assert!(ChaCha20Legacy::new_from_slices(&KEY_LONG, &[0; 12]).is_err());
+        assert!(ChaCha20Legacy::new_from_slices(&KEY_LONG[..16], &IV_LONG).is_err());
+    }
+}
+
+#[cfg(feature = "rng")]
+mod rng {
+    use chacha20::{
+        rand_core::{RngCore, SeedableRng},
+        ChaCha20Core, ChaCha20Rng, ChaCha8Rng,
+    };
+    use hex_literal::hex;
+
+    // RFC 8439 section 2.3.2 block function test vector, as output words.
+    // These checks hold independently of the host endianness.
+    #[test]
+    fn rng_words_are_little_endian() {
+        let seed = hex!("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        let mut rng = ChaCha20Rng::from_seed_with_stream(seed, hex!("000000090000004a00000000"));
+        rng.set_block_pos(1);
+
+        let expected_words = [
+            0xe4e7f110, 0x15593bd1, 0x1fdd0f50, 0xc47120a3, 0xc7f4d1c7, 0x0368c033, 0x9aaa2204,
+            0x4e6cd4c3, 0x466482d2, 0x09aa9f07, 0x05d7c214, 0xa2028bd9, 0xd19c12b5, 0xb94e16de,
+            0xe883d0cb, 0x4e3c50a2,
+        ];
+        let mut words = [0u32; 16];
+        for word in words.iter_mut() {
+            *word = rng.next_u32();
+        }
+        assert_eq!(words, expected_words);
+
+        rng.set_block_pos(1);
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        for (chunk, word) in bytes.chunks_exact(4).zip(expected_words) {
+            assert_eq!(chunk, word.to_le_bytes());
+        }
+    }
+
+    // The block counter is word 12 only. Wrapping it must not carry into the
+    // stream words, whichever backend generates the blocks.
+    #[test]
+    fn rng_block_counter_wraps_in_32_bits() {
+        let seed = [0x42; 32];
+        let stream = [1u32, 2, 3];
+
+        let mut rng = ChaCha20Rng::from_seed_with_stream(seed, stream);
+        let mut first_block = [0u32; 16];
+        first_block.iter_mut().for_each(|w| *w = rng.next_u32());
+
+        rng.set_block_pos(u32::MAX - 1);
+        let mut blocks = [0u32; 64];
+        blocks.iter_mut().for_each(|w| *w = rng.next_u32());
+        assert_eq!(blocks[32..48], first_block);
+        assert_eq!(
+            rng.get_stream(),
+            ChaCha20Rng::from_seed_with_stream(seed, stream).get_stream()
+        );
+    }
+
+    #[cfg(feature = "dev-backend-switch")]
+    #[test]
+    fn rng_all_backends() {
+        use chacha20::ChaChaBackend;
+
+        let seed = [0x42; 32];
+        let mut expected = [0u32; 1000];
+        let mut rng = ChaCha20Rng::from_seed_with_stream(seed, 7);
+        expected.iter_mut().for_each(|w| *w = rng.next_u32());
+
+        for backend in ChaChaBackend::ALL.into_iter().filter(|b| b.is_available()) {
+            let mut rng = ChaCha20Rng::from_seed_with_stream(seed, 7).with_backend(backend);
+            for &word in expected.iter() {
+                assert_eq!(rng.next_u32(), word, "{backend:?}");
+            }
+        }
+    }
+
+    #[cfg(feature = "cipher")]
+    #[test]
+    fn rng_matches_cipher_keystream() {
+        use chacha20::{cipher::StreamCipher, ChaCha20, KeyIvInit};
+
+        let seed = [0x42; 32];
+        let nonce = [0x24; 12];
+        let mut rng = ChaCha20Rng::from_seed_with_stream(seed, nonce);
+        let mut actual = [0u8; 1000];
+        rng.fill_bytes(&mut actual);
+
+        let mut expected = [0u8; 1000];
+        ChaCha20::new(&seed.into(), &nonce.into()).apply_keystream(&mut expected);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn debug_is_redacted() {
+        let seed = [0x42; 32];
+        let rng = ChaCha20Rng::from_seed(seed);
+        assert_eq!(format!("{:?}", rng), "ChaCha20Rng { .. }");
+        assert_eq!(
+            format!("{:?}", ChaCha8Rng::from_seed(seed)),
+            "ChaCha8Rng { .. }"
+        );
+
+        let seed = <ChaCha20Core as SeedableRng>::Seed::from(seed);
+        assert_eq!(format!("{:?}", seed), "Seed { .. }");
+
+        assert_eq!(
+            format!("{:?}", rng.snapshot()),
+            "ChaChaSnapshot { stream: 0, word_pos: 0, .. }"
+        );
+    }
+}
+
+#[cfg(feature = "squeezer")]
+mod squeezer {
+    use chacha20::{ChaCha20, ChaChaSqueezer, KeyIvInit};
+    use cipher::StreamCipher;
+
+    const KEY: [u8; 32] = [0x42; 32];
+
+    fn squeeze(squeezer: &mut ChaChaSqueezer) -> [u8; 100] {
+        let mut out = [0u8; 100];
+        squeezer.squeeze(&mut out[..30]);
+        squeezer.squeeze(&mut out[30..]);
+        out
+    }
+
+    #[test]
+    fn domains_are_distinct_and_reproducible() {
+        let mut squeezer = ChaChaSqueezer::new(&KEY.into());
+        let domain0 = squeeze(&mut squeezer);
+        squeezer.next_domain(1);
+        let domain1 = squeeze(&mut squeezer);
+        squeezer.next_domain(2);
+        let domain2 = squeeze(&mut squeezer);
+        assert_ne!(domain0, domain1);
+        assert_ne!(domain1, domain2);
+        assert_ne!(domain0, domain2);
+
+        squeezer.next_domain(1);
+        assert_eq!(squeeze(&mut squeezer), domain1);
+        squeezer.next_domain(0);
+        assert_eq!(squeeze(&mut squeezer), domain0);
+    }
+
+    #[test]
+    fn matches_chacha20_keystream() {
+        let mut squeezer = ChaChaSqueezer::new(&KEY.into());
+        squeezer.next_domain(0x0102_0304_0506_0708);
+
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&[8, 7, 6, 5, 4, 3, 2, 1]);
+        let mut expected = [0u8; 100];
+        ChaCha20::new(&KEY.into(), &nonce.into()).apply_keystream(&mut expected);
+        assert_eq!(squeeze(&mut squeezer), expected);
+    }
 }
@@ -60,10 +60,10 @@
 pub use cipher;
 
 use cipher::{
-    consts::{U1, U32, U4},
-    AlgorithmName, Block, BlockSizeUser, Iv, IvSizeUser, Key, KeyIvInit, KeySizeUser,
+    consts::{U16, U32, U4},
+    AlgorithmName, Block, BlockSizeUser, Iv, IvSizeUser, Key, KeyIvInit, KeySizeUser, ParBlocks,
     ParBlocksSizeUser, StreamCipherBackend, StreamCipherClosure, StreamCipherCore,
-    StreamCipherCoreWrapper,
+    StreamCipherCoreWrapper, StreamCipherSeekCore,
 };
 use core::fmt;
 
@@ -81,11 +81,30 @@ const IV_WORDS: usize = IV_BITS / 32;
 /// The HC-256 stream cipher core
 pub type Hc256 = StreamCipherCoreWrapper<Hc256Core>;
 
+/// The HC-256 stream cipher, supporting seeking.
+///
+/// The keystream generation of HC-256 updates its tables in a way which can
+/// not be inverted or skipped ahead, so seeking via
+/// [`StreamCipherSeek`][cipher::StreamCipherSeek] restores the tables saved
+/// right after the initialization and regenerates every keystream word up to
+/// the target position. The cost of a seek is thus linear in the target
+/// position, similar to generating the keystream up to that position. Unless
+/// seeking is needed, prefer [`Hc256`], which does not keep the additional
+/// 8 KiB of saved tables.
+pub type Hc256Seekable = StreamCipherCoreWrapper<Hc256SeekableCore>;
+
 /// The HC-256 stream cipher core
+///
+/// When the `zeroize` feature is enabled, the internal state is zeroized on
+/// drop. The scratch buffer used during initialization holds the expanded
+/// key and IV, from which the initial tables are taken, so it is zeroized as
+/// well before the initialization returns.
 pub struct Hc256Core {
     ptable: [u32; TABLE_SIZE],
     qtable: [u32; TABLE_SIZE],
     idx: u32,
+    /// Key words, used for IV reinitialization. `None` for an imported state.
+    key: Option<[u32; KEY_WORDS]>,
 }
 
 impl BlockSizeUser for Hc256Core {
@@ -102,27 +121,103 @@ impl IvSizeUser for Hc256Core {
 
 impl KeyIvInit for Hc256Core {
     fn new(key: &Key<Self>, iv: &Iv<Self>) -> Self {
-        fn f1(x: u32) -> u32 {
-            x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
-        }
-
-        fn f2(x: u32) -> u32 {
-            x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
-        }
-
         let mut out = Self {
             ptable: [0; TABLE_SIZE],
             qtable: [0; TABLE_SIZE],
             idx: 0,
+            key: None,
         };
-        let mut data = [0; INIT_SIZE];
 
+        let mut key_words = [0; KEY_WORDS];
         for i in 0..KEY_WORDS {
-            data[i] = key[4 * i] as u32 & 0xff
+            key_words[i] = key[4 * i] as u32 & 0xff
                 | (key[(4 * i) + 1] as u32 & 0xff) << 8
                 | (key[(4 * i) + 2] as u32 & 0xff) << 16
                 | (key[(4 * i) + 3] as u32 & 0xff) << 24;
         }
+        out.key = Some(key_words);
+        #[cfg(feature = "zeroize")]
+        key_words.zeroize();
+
+        out.init(iv);
+        out
+    }
+}
+
+impl Hc256Core {
+    /// Reinitialize the cipher with a new IV, keeping the key.
+    ///
+    /// Note that HC-256 mixes the key and the IV from the first step of its
+    /// initialization, so the full initialization, including the 4096 warmup
+    /// steps, has to run again. This only saves the key parsing, but allows
+    /// reusing an instance for many messages under one key.
+    ///
+    /// # Panics
+    ///
+    /// If the core was created with `Hc256Core::from_state`, since the key
+    /// is not known then.
+    pub fn reinit(&mut self, iv: &Iv<Self>) {
+        assert!(
+            self.key.is_some(),
+            "HC-256 core imported from a state has no key to reinitialize with"
+        );
+        self.init(iv);
+    }
+
+    /// Returns an iterator over the keystream as 32-bit words.
+    ///
+    /// This gives direct access to the output words of HC-256, e.g. for
+    /// statistical analysis, without converting them to bytes. The
+    /// iterator never ends.
+    pub fn keystream_words(&mut self) -> impl Iterator<Item = u32> + '_ {
+        core::iter::from_fn(move || Some(self.gen_word()))
+    }
+
+    /// Export the internal state as `(ptable, qtable, idx)`.
+    ///
+    /// This is meant for differential testing and fuzzing against other
+    /// implementations, and is not covered by semver guarantees.
+    #[cfg(feature = "unstable-internals")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unstable-internals")))]
+    pub fn export_state(&self) -> ([u32; TABLE_SIZE], [u32; TABLE_SIZE], u32) {
+        (self.ptable, self.qtable, self.idx)
+    }
+
+    /// Create a core from a state returned by [`Hc256Core::export_state`]
+    /// or taken from another implementation. Only the low 11 bits of `idx`
+    /// are used.
+    ///
+    /// The key is not known, so [`Hc256Core::reinit`] panics on the returned
+    /// core.
+    ///
+    /// This is meant for differential testing and fuzzing against other
+    /// implementations, and is not covered by semver guarantees.
+    #[cfg(feature = "unstable-internals")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "unstable-internals")))]
+    pub fn from_state(ptable: [u32; TABLE_SIZE], qtable: [u32; TABLE_SIZE], idx: u32) -> Self {
+        Self {
+            ptable,
+            qtable,
+            idx: idx & (2048 - 1),
+            key: None,
+        }
+    }
+
+    fn init(&mut self, iv: &Iv<Self>) {
+        fn f1(x: u32) -> u32 {
+            x.rotate_right(7) ^ x.rotate_right(18) ^ (x >> 3)
+        }
+
+        fn f2(x: u32) -> u32 {
+            x.rotate_right(17) ^ x.rotate_right(19) ^ (x >> 10)
+        }
+
+        let mut data = [0; INIT_SIZE];
+
+        let Some(key) = &self.key else {
+            unreachable!("only cores with a key are initialized")
+        };
+        data[..KEY_WORDS].copy_from_slice(key);
 
         for i in 0..IV_WORDS {
             data[i + KEY_WORDS] = iv[4 * i] as u32 & 0xff
@@ -139,16 +234,17 @@ impl KeyIvInit for Hc256Core {
                 .wrapping_add(i as u32);
         }
 
-        out.ptable[..TABLE_SIZE].clone_from_slice(&data[512..(TABLE_SIZE + 512)]);
-        out.qtable[..TABLE_SIZE].clone_from_slice(&data[1536..(TABLE_SIZE + 1536)]);
+        self.ptable[..TABLE_SIZE].clone_from_slice(&data[512..(TABLE_SIZE + 512)]);
+        self.qtable[..TABLE_SIZE].clone_from_slice(&data[1536..(TABLE_SIZE + 1536)]);
 
-        out.idx = 0;
+        self.idx = 0;
 
         for _ in 0..4096 {
-            out.gen_word();
+            self.gen_word();
         }
 
-        out
+        #[cfg(feature = "zeroize")]
+        data.zeroize();
     }
 }
 
@@ -239,6 +335,7 @@ impl Drop for Hc256Core {
         self.ptable.zeroize();
         self.qtable.zeroize();
         self.idx.zeroize();
+        self.key.zeroize();
     }
 }
 
@@ -246,6 +343,122 @@ impl Drop for Hc256Core {
 #[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
 impl ZeroizeOnDrop for Hc256Core {}
 
+/// Core state of the seekable HC-256 stream cipher.
+pub struct Hc256SeekableCore {
+    core: Hc256Core,
+    /// State right after the initialization, used for seeking
+    init_ptable: [u32; TABLE_SIZE],
+    init_qtable: [u32; TABLE_SIZE],
+    init_idx: u32,
+    /// Number of generated keystream words
+    word_pos: u64,
+}
+
+impl BlockSizeUser for Hc256SeekableCore {
+    type BlockSize = U4;
+}
+
+impl KeySizeUser for Hc256SeekableCore {
+    type KeySize = U32;
+}
+
+impl IvSizeUser for Hc256SeekableCore {
+    type IvSize = U32;
+}
+
+impl KeyIvInit for Hc256SeekableCore {
+    fn new(key: &Key<Self>, iv: &Iv<Self>) -> Self {
+        let mut out = Self {
+            core: Hc256Core::new(key, iv),
+            init_ptable: [0; TABLE_SIZE],
+            init_qtable: [0; TABLE_SIZE],
+            init_idx: 0,
+            word_pos: 0,
+        };
+        out.save_init_state();
+        out
+    }
+}
+
+impl Hc256SeekableCore {
+    /// Reinitialize the cipher with a new IV, keeping the key.
+    ///
+    /// See [`Hc256Core::reinit`]. The position is reset to zero, and seeking
+    /// afterwards is relative to the new IV.
+    pub fn reinit(&mut self, iv: &Iv<Self>) {
+        self.core.reinit(iv);
+        self.save_init_state();
+    }
+
+    fn save_init_state(&mut self) {
+        self.init_ptable = self.core.ptable;
+        self.init_qtable = self.core.qtable;
+        self.init_idx = self.core.idx;
+        self.word_pos = 0;
+    }
+}
+
+impl StreamCipherCore for Hc256SeekableCore {
+    #[inline(always)]
+    fn remaining_blocks(&self) -> Option<usize> {
+        None
+    }
+
+    fn process_with_backend(&mut self, f: impl StreamCipherClosure<BlockSize = Self::BlockSize>) {
+        f.call(&mut CountingBackend {
+            inner: &mut Backend(&mut self.core),
+            pos: &mut self.word_pos,
+        });
+    }
+}
+
+impl StreamCipherSeekCore for Hc256SeekableCore {
+    type Counter = u64;
+
+    #[inline(always)]
+    fn get_block_pos(&self) -> u64 {
+        self.word_pos
+    }
+
+    fn set_block_pos(&mut self, pos: u64) {
+        self.core.ptable = self.init_ptable;
+        self.core.qtable = self.init_qtable;
+        self.core.idx = self.init_idx;
+
+        for _ in 0..pos {
+            self.core.gen_word();
+        }
+        self.word_pos = pos;
+    }
+}
+
+impl AlgorithmName for Hc256SeekableCore {
+    fn write_alg_name(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Hc256")
+    }
+}
+
+impl fmt::Debug for Hc256SeekableCore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Hc256SeekableCore { ... }")
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl Drop for Hc256SeekableCore {
+    fn drop(&mut self) {
+        self.init_ptable.zeroize();
+        self.init_qtable.zeroize();
+        self.init_idx.zeroize();
+        self.word_pos.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl ZeroizeOnDrop for Hc256SeekableCore {}
+
 struct Backend<'a>(&'a mut Hc256Core);
 
 impl BlockSizeUser for Backend<'_> {
@@ -253,7 +466,7 @@ impl BlockSizeUser for Backend<'_> {
 }
 
 impl ParBlocksSizeUser for Backend<'_> {
-    type ParBlocksSize = U1;
+    type ParBlocksSize = U16;
 }
 
 impl StreamCipherBackend for Backend<'_> {
@@ -261,4 +474,41 @@ impl StreamCipherBackend for Backend<'_> {
     fn gen_ks_block(&mut self, block: &mut Block<Self>) {
         block.copy_from_slice(&self.0.gen_word().to_le_bytes());
     }
+
+    /// Words still have to be generated sequentially, but batching them
+    /// reduces the per-word overhead of the wrapper.
+    #[inline(always)]
+    fn gen_par_ks_blocks(&mut self, blocks: &mut ParBlocks<Self>) {
+        for block in blocks.iter_mut() {
+            block.copy_from_slice(&self.0.gen_word().to_le_bytes());
+        }
+    }
+}
+
+/// Backend which counts the generated keystream words.
+struct CountingBackend<'a, 'b> {
+    inner: &'a mut Backend<'b>,
+    pos: &'a mut u64,
+}
+
+impl BlockSizeUser for CountingBackend<'_, '_> {
+    type BlockSize = <Hc256Core as BlockSizeUser>::BlockSize;
+}
+
+impl ParBlocksSizeUser for CountingBackend<'_, '_> {
+    type ParBlocksSize = <Backend<'static> as ParBlocksSizeUser>::ParBlocksSize;
+}
+
+impl StreamCipherBackend for CountingBackend<'_, '_> {
+    #[inline(always)]
+    fn gen_ks_block(&mut self, block: &mut Block<Self>) {
+        self.inner.gen_ks_block(block);
+        *self.pos = self.pos.wrapping_add(1);
+    }
+
+    #[inline(always)]
+    fn gen_par_ks_blocks(&mut self, blocks: &mut ParBlocks<Self>) {
+        self.inner.gen_par_ks_blocks(blocks);
+        *self.pos = self.pos.wrapping_add(blocks.len() as u64);
+    }
 }
@@ -0,0 +1,8 @@
+//! Property tests checking that encrypting in arbitrary chunks matches
+//! encrypting in a single call.
+
+use hc_256::{Hc256, Hc256Seekable};
+use stream_ciphers_test_helpers::chunked_test;
+
+chunked_test!(hc256_chunked, Hc256, key: 32, iv: 32);
+chunked_test!(hc256_seekable_chunked, Hc256Seekable, key: 32, iv: 32);
@@ -1,5 +1,5 @@
-use cipher::{KeyIvInit, StreamCipher};
-use hc_256::Hc256;
+use cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+use hc_256::{Hc256, Hc256Seekable};
 use hex_literal::hex;
 
 const KEY_BYTES: usize = 256 / 8;
@@ -76,3 +76,140 @@ fn test_hc256_key1_iv0() {
         assert!(buf.iter().all(|&v| v == 0));
     }
 }
+
+#[test]
+fn test_hc256_seek() {
+    let mut cipher = Hc256Seekable::new(&KEY1.into(), &IV0.into());
+    for pos in (0..EXPECTED_KEY1_IV0.len()).rev() {
+        cipher.seek(pos);
+        assert_eq!(cipher.current_pos::<usize>(), pos);
+
+        let mut buf = EXPECTED_KEY1_IV0;
+        cipher.apply_keystream(&mut buf[pos..]);
+        assert!(buf[pos..].iter().all(|&v| v == 0));
+        assert_eq!(cipher.current_pos::<usize>(), buf.len());
+    }
+
+    // positions beyond the 2048 word period of the table index
+    let mut expected = [0u8; 10_000];
+    Hc256::new(&KEY0.into(), &IV1.into()).apply_keystream(&mut expected);
+    for pos in [8191, 8192, 8193, 9000, 4097] {
+        let mut buf = [0u8; 1000];
+        let mut cipher = Hc256Seekable::new(&KEY0.into(), &IV1.into());
+        cipher.seek(pos);
+        cipher.apply_keystream(&mut buf);
+        assert_eq!(buf[..], expected[pos..][..1000]);
+    }
+}
+
+#[test]
+fn test_hc256_par_blocks() {
+    // processing 4 bytes at a time only generates single blocks
+    let mut expected = [0u8; 1000];
+    let mut cipher = Hc256::new(&KEY1.into(), &IV1.into());
+    for chunk in expected.chunks_mut(4) {
+        cipher.apply_keystream(chunk);
+    }
+
+    let mut buf = [0u8; 1000];
+    let mut cipher = Hc256::new(&KEY1.into(), &IV1.into());
+    cipher.apply_keystream(&mut buf);
+    assert_eq!(buf, expected);
+
+    // the seekable core counts the words of batched blocks
+    let mut buf = [0u8; 1000];
+    let mut cipher = Hc256Seekable::new(&KEY1.into(), &IV1.into());
+    cipher.apply_keystream(&mut buf);
+    assert_eq!(buf, expected);
+    assert_eq!(cipher.current_pos::<usize>(), buf.len());
+}
+
+#[test]
+fn test_hc256_reinit() {
+    use cipher::StreamCipherCore;
+    use hc_256::{Hc256Core, Hc256SeekableCore};
+
+    let mut core = Hc256Core::new(&KEY0.into(), &IV1.into());
+    core.write_keystream_block(&mut Default::default());
+
+    core.reinit(&IV0.into());
+    let mut buf = EXPECTED_KEY0_IV0;
+    Hc256::from_core(core).apply_keystream(&mut buf);
+    assert!(buf.iter().all(|&v| v == 0));
+
+    let mut core = Hc256Core::new(&KEY0.into(), &IV0.into());
+    core.reinit(&IV1.into());
+    let mut buf = EXPECTED_KEY0_IV1;
+    Hc256::from_core(core).apply_keystream(&mut buf);
+    assert!(buf.iter().all(|&v| v == 0));
+
+    // reinitializing resets the position, and seeking uses the new IV
+    let mut core = Hc256SeekableCore::new(&KEY0.into(), &IV0.into());
+    core.write_keystream_block(&mut Default::default());
+    core.reinit(&IV1.into());
+    let mut cipher = Hc256Seekable::from_core(core);
+    assert_eq!(cipher.current_pos::<usize>(), 0);
+    cipher.seek(8);
+    let mut buf = EXPECTED_KEY0_IV1;
+    cipher.apply_keystream(&mut buf[8..]);
+    assert!(buf[8..].iter().all(|&v| v == 0));
+}
+
+#[test]
+fn test_hc256_keystream_words() {
+    use hc_256::Hc256Core;
+
+    let mut core = Hc256Core::new(&KEY1.into(), &IV0.into());
+    // the KAT goes first so no additional word is generated at its end
+    let expected = EXPECTED_KEY1_IV0.chunks_exact(4);
+    for (chunk, word) in expected.zip(core.keystream_words()) {
+        assert_eq!(word.to_le_bytes(), chunk);
+    }
+
+    // the cipher continues right after the returned words
+    let mut expected = [0u8; 128];
+    Hc256::new(&KEY1.into(), &IV0.into()).apply_keystream(&mut expected);
+    let mut buf = [0u8; 64];
+    Hc256::from_core(core).apply_keystream(&mut buf);
+    assert_eq!(buf, expected[64..]);
+}
+
+#[test]
+#[cfg(feature = "unstable-internals")]
+fn test_hc256_export_import_state() {
+    use hc_256::Hc256Core;
+
+    let mut core = Hc256Core::new(&KEY1.into(), &IV1.into());
+    // stop in the middle of the Q table half of the 2048 word period
+    core.keystream_words().take(1500).for_each(drop);
+
+    let (ptable, qtable, idx) = core.export_state();
+    let mut imported = Hc256Core::from_state(ptable, qtable, idx);
+    assert_eq!(imported.export_state(), core.export_state());
+
+    let expected: Vec<u32> = core.keystream_words().take(3000).collect();
+    let actual: Vec<u32> = imported.keystream_words().take(3000).collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+#[cfg(feature = "unstable-internals")]
+#[should_panic]
+fn test_hc256_imported_state_cannot_reinit() {
+    use hc_256::Hc256Core;
+
+    let (ptable, qtable, idx) = Hc256Core::new(&KEY1.into(), &IV1.into()).export_state();
+    let mut imported = Hc256Core::from_state(ptable, qtable, idx);
+    imported.reinit(&IV0.into());
+}
+
+#[test]
+fn test_hc256_debug_is_redacted() {
+    use hc_256::{Hc256Core, Hc256SeekableCore};
+
+    let core = Hc256Core::new(&KEY1.into(), &IV1.into());
+    assert_eq!(format!("{:?}", core), "Hc256Core { ... }");
+
+    let core = Hc256SeekableCore::new(&KEY1.into(), &IV1.into());
+    assert_eq!(format!("{:?}", core), "Hc256SeekableCore { ... }");
+}
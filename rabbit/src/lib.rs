@@ -60,15 +60,24 @@
 pub use cipher;
 
 use cipher::{
-    consts::{U1, U16, U8},
+    consts::{U16, U4, U8},
     crypto_common::InnerUser,
-    Block, BlockSizeUser, InnerIvInit, IvSizeUser, KeyInit, KeySizeUser, ParBlocksSizeUser,
-    StreamCipherBackend, StreamCipherClosure, StreamCipherCore, StreamCipherCoreWrapper,
+    Block, BlockSizeUser, InnerIvInit, IvSizeUser, KeyInit, KeySizeUser, ParBlocks,
+    ParBlocksSizeUser, StreamCipherBackend, StreamCipherClosure, StreamCipherCore,
+    StreamCipherCoreWrapper, StreamCipherSeekCore,
 };
 
 #[cfg(feature = "zeroize")]
 use cipher::zeroize::{Zeroize, ZeroizeOnDrop};
 
+#[cfg(feature = "rng")]
+mod rng;
+
+#[cfg(feature = "rng")]
+pub use rand_core;
+#[cfg(feature = "rng")]
+pub use rng::{RabbitRng, RabbitRngCore};
+
 /// RFC 4503. 2.3.  Key Setup Scheme (page 2).
 const KEY_BYTE_LEN: usize = 16;
 /// RFC 4503. 2.4.  IV Setup Scheme (page 2-3).
@@ -91,8 +100,16 @@ pub type Iv = cipher::Iv<RabbitCore>;
 type BlockSize = U16;
 
 /// The Rabbit stream cipher initializied only with key.
+///
+/// See [`Rabbit`] for the cost of seeking.
 pub type RabbitKeyOnly = StreamCipherCoreWrapper<RabbitKeyOnlyCore>;
 /// The Rabbit stream cipher initializied with key and IV.
+///
+/// Rabbit's next-state function can not be inverted or skipped ahead, so
+/// seeking via [`StreamCipherSeek`][cipher::StreamCipherSeek] restarts from
+/// the state right after the initialization and re-runs the next-state
+/// function once per 16-byte block. The cost of a seek is thus linear in the
+/// target position, similar to generating the keystream up to that position.
 pub type Rabbit = StreamCipherCoreWrapper<RabbitCore>;
 
 /// RFC 4503. 2.2.  Inner State (page 2).
@@ -248,6 +265,15 @@ impl State {
         self.next_state();
         self.extract()
     }
+
+    /// Returns the state reached after generating `blocks` keystream blocks.
+    fn advanced(&self, blocks: u64) -> Self {
+        let mut state = self.clone();
+        for _ in 0..blocks {
+            state.next_state();
+        }
+        state
+    }
 }
 
 #[cfg(feature = "zeroize")]
@@ -262,7 +288,10 @@ impl core::ops::Drop for State {
 /// Core state of the Rabbit stream cipher initialized only with key.
 #[derive(Clone)]
 pub struct RabbitKeyOnlyCore {
+    /// State right after the key setup, used for seeking
+    initial: State,
     state: State,
+    block_num: u64,
 }
 
 impl KeySizeUser for RabbitKeyOnlyCore {
@@ -271,8 +300,11 @@ impl KeySizeUser for RabbitKeyOnlyCore {
 
 impl KeyInit for RabbitKeyOnlyCore {
     fn new(key: &Key) -> Self {
+        let state = State::setup_key((*key).into());
         Self {
-            state: State::setup_key((*key).into()),
+            initial: state.clone(),
+            state,
+            block_num: 0,
         }
     }
 }
@@ -284,14 +316,29 @@ impl BlockSizeUser for RabbitKeyOnlyCore {
 impl StreamCipherCore for RabbitKeyOnlyCore {
     #[inline(always)]
     fn remaining_blocks(&self) -> Option<usize> {
-        // Rabbit can generate 2^64 blocks, but since it does not implement
-        // the seeking traits, we can assume that so many blocks never will
-        // be processed
-        None
+        let rem = u64::MAX - self.block_num;
+        rem.try_into().ok()
     }
 
     fn process_with_backend(&mut self, f: impl StreamCipherClosure<BlockSize = Self::BlockSize>) {
-        f.call(&mut Backend(&mut self.state));
+        f.call(&mut Backend {
+            state: &mut self.state,
+            block_num: &mut self.block_num,
+        });
+    }
+}
+
+impl StreamCipherSeekCore for RabbitKeyOnlyCore {
+    type Counter = u64;
+
+    #[inline(always)]
+    fn get_block_pos(&self) -> u64 {
+        self.block_num
+    }
+
+    fn set_block_pos(&mut self, pos: u64) {
+        self.state = self.initial.advanced(pos);
+        self.block_num = pos;
     }
 }
 
@@ -302,7 +349,41 @@ impl ZeroizeOnDrop for RabbitKeyOnlyCore {}
 /// Core state of the Rabbit stream cipher initialized with key and IV.
 #[derive(Clone)]
 pub struct RabbitCore {
+    /// State right after the key setup, used for IV reinitialization
+    master: State,
+    /// State right after the IV setup, used for seeking
+    initial: State,
     state: State,
+    block_num: u64,
+}
+
+impl RabbitCore {
+    /// Advance the state and return the next 16-byte keystream block.
+    ///
+    /// Unlike applying the keystream with [`Rabbit`], this works on whole
+    /// blocks only and does not XOR the keystream into any data, which makes
+    /// it suitable for building custom constructions on top of Rabbit.
+    /// Since the core has no partial-block buffer, it is always at a block
+    /// boundary.
+    #[inline]
+    pub fn keystream_block(&mut self) -> [u8; 16] {
+        let mut block = Block::<Self>::default();
+        self.write_keystream_block(&mut block);
+        block.into()
+    }
+
+    /// Reinitialize the cipher with a new IV, keeping the key.
+    ///
+    /// The state after the key setup is stored in the core, so this only runs
+    /// the IV setup and is cheaper than creating a new instance with the same
+    /// key, which is useful when encrypting many messages under one key.
+    pub fn reinit(&mut self, iv: &Iv) {
+        let mut state = self.master.clone();
+        state.setup_iv((*iv).into());
+        self.initial = state.clone();
+        self.state = state;
+        self.block_num = 0;
+    }
 }
 
 impl InnerUser for RabbitCore {
@@ -315,9 +396,15 @@ impl IvSizeUser for RabbitCore {
 
 impl InnerIvInit for RabbitCore {
     fn inner_iv_init(inner: RabbitKeyOnlyCore, iv: &Iv) -> Self {
-        let mut state = inner.state;
+        let master = inner.state;
+        let mut state = master.clone();
         state.setup_iv((*iv).into());
-        Self { state }
+        Self {
+            master,
+            initial: state.clone(),
+            state,
+            block_num: 0,
+        }
     }
 }
 
@@ -328,34 +415,86 @@ impl BlockSizeUser for RabbitCore {
 impl StreamCipherCore for RabbitCore {
     #[inline(always)]
     fn remaining_blocks(&self) -> Option<usize> {
-        // Rabbit can generate 2^64 blocks, but since it does not implement
-        // the seeking traits, we can assume that so many blocks never will
-        // be processed
-        None
+        let rem = u64::MAX - self.block_num;
+        rem.try_into().ok()
     }
 
     fn process_with_backend(&mut self, f: impl StreamCipherClosure<BlockSize = Self::BlockSize>) {
-        f.call(&mut Backend(&mut self.state));
+        f.call(&mut Backend {
+            state: &mut self.state,
+            block_num: &mut self.block_num,
+        });
+    }
+}
+
+impl StreamCipherSeekCore for RabbitCore {
+    type Counter = u64;
+
+    #[inline(always)]
+    fn get_block_pos(&self) -> u64 {
+        self.block_num
+    }
+
+    fn set_block_pos(&mut self, pos: u64) {
+        self.state = self.initial.advanced(pos);
+        self.block_num = pos;
     }
 }
 
-struct Backend<'a>(&'a mut State);
+struct Backend<'a> {
+    state: &'a mut State,
+    block_num: &'a mut u64,
+}
 
 impl BlockSizeUser for Backend<'_> {
     type BlockSize = BlockSize;
 }
 
 impl ParBlocksSizeUser for Backend<'_> {
-    type ParBlocksSize = U1;
+    type ParBlocksSize = U4;
 }
 
 impl StreamCipherBackend for Backend<'_> {
     #[inline(always)]
     fn gen_ks_block(&mut self, block: &mut Block<Self>) {
-        block.copy_from_slice(&self.0.next_block());
+        block.copy_from_slice(&self.state.next_block());
+        *self.block_num = self.block_num.wrapping_add(1);
+    }
+
+    /// Blocks still have to be generated sequentially, but batching them
+    /// reduces the per-block overhead of the wrapper.
+    #[inline(always)]
+    fn gen_par_ks_blocks(&mut self, blocks: &mut ParBlocks<Self>) {
+        for block in blocks.iter_mut() {
+            block.copy_from_slice(&self.state.next_block());
+        }
+        *self.block_num = self.block_num.wrapping_add(blocks.len() as u64);
     }
 }
 
 #[cfg(feature = "zeroize")]
 #[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
 impl ZeroizeOnDrop for RabbitCore {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cipher::{KeyIvInit, StreamCipher, StreamCipherSeek};
+
+    #[test]
+    fn remaining_blocks() {
+        let mut core = RabbitCore::new(&Key::default(), &Iv::default());
+        assert_eq!(core.remaining_blocks(), u64::MAX.try_into().ok());
+
+        // seeking is linear in the position, so the counter is set directly
+        core.block_num = u64::MAX - 2;
+        assert_eq!(core.remaining_blocks(), Some(2));
+
+        let mut cipher = Rabbit::from_core(core);
+        assert!(cipher.try_apply_keystream(&mut [0; 20]).is_ok());
+        assert_eq!(cipher.get_core().remaining_blocks(), Some(0));
+        assert!(cipher.try_apply_keystream(&mut [0; 12]).is_ok());
+        assert!(cipher.try_apply_keystream(&mut [0; 1]).is_err());
+        assert_eq!(cipher.current_pos::<u128>(), 16 * u128::from(u64::MAX));
+    }
+}
@@ -0,0 +1,214 @@
+//! Random number generator based on the Rabbit stream cipher.
+
+use core::fmt::Debug;
+
+use cipher::{KeyIvInit, StreamCipherCore};
+use rand_core::{
+    block::{BlockRng, BlockRngCore, CryptoBlockRng},
+    impl_try_rng_from_rng_core, CryptoRng, RngCore, SeedableRng,
+};
+
+#[cfg(feature = "zeroize")]
+use cipher::zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{Iv, Key, RabbitCore};
+
+// number of 32-bit words per Rabbit block (fixed by algorithm definition)
+const BLOCK_WORDS: usize = 4;
+
+/// The results buffer that zeroizes on drop when the `zeroize` feature is enabled.
+#[derive(Clone, Default)]
+pub struct BlockRngResults([u32; BLOCK_WORDS]);
+
+impl AsRef<[u32]> for BlockRngResults {
+    fn as_ref(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl AsMut<[u32]> for BlockRngResults {
+    fn as_mut(&mut self) -> &mut [u32] {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for BlockRngResults {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// A cryptographically secure random number generator that uses the Rabbit
+/// stream cipher.
+///
+/// The RNG is seeded with a 128-bit key and uses an all-zero IV, unless
+/// created with [`RabbitRng::from_key_iv`]. Output is the Rabbit keystream,
+/// buffered one 16-byte block at a time, and [`BlockRng`] is used to
+/// implement the [`RngCore`] methods.
+///
+/// # Example
+///
+/// ```rust
+/// use rabbit::RabbitRng;
+/// // use rand_core traits
+/// use rabbit::rand_core::{RngCore, SeedableRng};
+///
+/// let mut rng = RabbitRng::from_seed([42u8; 16]);
+/// let x = rng.next_u32();
+/// let mut array = [0u8; 32];
+/// rng.fill_bytes(&mut array);
+///
+/// let mut rng = RabbitRng::from_key_iv([42u8; 16], [24u8; 8]);
+/// let y = rng.next_u64();
+/// ```
+#[derive(Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "rng")))]
+pub struct RabbitRng {
+    core: BlockRng<RabbitRngCore>,
+}
+
+/// The Rabbit core random number generator
+#[derive(Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "rng")))]
+pub struct RabbitRngCore(RabbitCore);
+
+impl RabbitRng {
+    /// Create a new RNG from a 128-bit key and a 64-bit IV.
+    #[inline]
+    pub fn from_key_iv(key: [u8; 16], iv: [u8; 8]) -> Self {
+        RabbitRngCore::from_key_iv(key, iv).into()
+    }
+}
+
+impl RabbitRngCore {
+    /// Create a new core RNG from a 128-bit key and a 64-bit IV.
+    #[inline]
+    pub fn from_key_iv(key: [u8; 16], iv: [u8; 8]) -> Self {
+        Self(RabbitCore::new(&Key::from(key), &Iv::from(iv)))
+    }
+}
+
+impl SeedableRng for RabbitRng {
+    type Seed = [u8; 16];
+
+    #[inline]
+    fn from_seed(seed: Self::Seed) -> Self {
+        RabbitRngCore::from_seed(seed).into()
+    }
+}
+
+impl SeedableRng for RabbitRngCore {
+    type Seed = [u8; 16];
+
+    #[inline]
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::from_key_iv(seed, Default::default())
+    }
+}
+
+impl BlockRngCore for RabbitRngCore {
+    type Item = u32;
+    type Results = BlockRngResults;
+
+    #[inline]
+    fn generate(&mut self, results: &mut Self::Results) {
+        let mut block = Default::default();
+        self.0.write_keystream_block(&mut block);
+
+        for (word, chunk) in results.0.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        #[cfg(feature = "zeroize")]
+        block.zeroize();
+    }
+}
+
+impl CryptoBlockRng for RabbitRngCore {}
+impl CryptoRng for RabbitRng {}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl ZeroizeOnDrop for RabbitRngCore {}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl ZeroizeOnDrop for RabbitRng {}
+
+// Custom Debug implementation that does not expose the internal state
+impl Debug for RabbitRng {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("RabbitRng { .. }")
+    }
+}
+
+impl RngCore for RabbitRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.core.next_u32()
+    }
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.core.next_u64()
+    }
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.core.fill_bytes(dest)
+    }
+}
+
+impl_try_rng_from_rng_core!(RabbitRng);
+
+impl From<RabbitRngCore> for RabbitRng {
+    fn from(core: RabbitRngCore) -> Self {
+        RabbitRng {
+            core: BlockRng::new(core),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    // RFC4503 Appendix A. A.2. Testing with IV Setup (page 7)
+    const KEYSTREAM_IV0: [u8; 48] = hex!(
+        "EDB70567375DCD7CD89554F85E27A7C6"
+        "8D4ADC7032298F7BD4EFF504ACA6295F"
+        "668FBF478ADB2BE51E6CDE292B82DE2A"
+    );
+    const IV1: [u8; 8] = hex!("597E26C175F573C3");
+    const KEYSTREAM_IV1: [u8; 48] = hex!(
+        "6D7D012292CCDCE0E2120058B94ECD1F"
+        "2E6F93EDFF99247B012521D1104E5FA7"
+        "A79B0212D0BD56233938E793C312C1EB"
+    );
+
+    #[test]
+    fn test_rabbit_rng_fill_bytes() {
+        let mut rng = RabbitRng::from_seed([0; 16]);
+        let mut buf = [0u8; 48];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, KEYSTREAM_IV0);
+
+        let mut rng = RabbitRng::from_key_iv([0; 16], IV1);
+        let mut buf = [0u8; 48];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, KEYSTREAM_IV1);
+    }
+
+    #[test]
+    fn test_rabbit_rng_next_words() {
+        let word = |i: usize| u32::from_le_bytes(KEYSTREAM_IV0[4 * i..][..4].try_into().unwrap());
+        let dword = |i: usize| u64::from(word(i)) | (u64::from(word(i + 1)) << 32);
+
+        let mut rng = RabbitRng::from_seed([0; 16]);
+        assert_eq!(rng.next_u32(), 0x6705_B7ED);
+        assert_eq!(rng.next_u64(), dword(1));
+        // crosses the block boundary
+        assert_eq!(rng.next_u64(), dword(3));
+        assert_eq!(rng.next_u32(), word(5));
+    }
+}
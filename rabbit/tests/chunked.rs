@@ -0,0 +1,8 @@
+//! Property tests checking that encrypting in arbitrary chunks matches
+//! encrypting in a single call.
+
+use rabbit::{Rabbit, RabbitKeyOnly};
+use stream_ciphers_test_helpers::chunked_test;
+
+chunked_test!(rabbit_chunked, Rabbit, key: 16, iv: 8);
+chunked_test!(rabbit_key_only_chunked, RabbitKeyOnly, key: 16);
@@ -1,4 +1,4 @@
-use cipher::{KeyInit, KeyIvInit, StreamCipher};
+use cipher::{KeyInit, KeyIvInit, StreamCipher, StreamCipherSeek};
 use hex_literal::hex;
 use rabbit::{Rabbit, RabbitKeyOnly};
 
@@ -83,3 +83,115 @@ fn test_rabbit_key_iv() {
         }
     }
 }
+
+#[test]
+fn test_rabbit_seek() {
+    // RFC4503 Appendix A. A.2. Testing with IV Setup (page 7)
+    let key = hex!("00000000000000000000000000000000");
+    let iv = hex!("597E26C175F573C3");
+    let ks = hex!(
+        "6D7D012292CCDCE0E2120058B94ECD1F"
+        "2E6F93EDFF99247B012521D1104E5FA7"
+        "A79B0212D0BD56233938E793C312C1EB"
+    );
+
+    let mut rabbit = Rabbit::new(&key.into(), &iv.into());
+    for pos in (0..ks.len()).rev() {
+        rabbit.seek(pos);
+        assert_eq!(rabbit.current_pos::<usize>(), pos);
+
+        let mut d = ks;
+        rabbit.apply_keystream(&mut d[pos..]);
+        assert!(d[pos..].iter().all(|&v| v == 0));
+        assert_eq!(rabbit.current_pos::<usize>(), ks.len());
+    }
+
+    // RFC4503 Appendix A. A.1. Testing without IV Setup (page 7)
+    let key = hex!("ACC351DCF162FC3BFE363D2E29132891");
+    let ks = hex!(
+        "9C51E28784C37FE9A127F63EC8F32D3D"
+        "19FC5485AA53BF96885B40F461CD76F5"
+        "5E4C4D20203BE58A5043DBFB737454E5"
+    );
+
+    let mut rabbit = RabbitKeyOnly::new(&key.into());
+    for pos in [17, 40, 0, 33, 16] {
+        rabbit.seek(pos);
+        let mut d = ks;
+        rabbit.apply_keystream(&mut d[pos..]);
+        assert!(d[pos..].iter().all(|&v| v == 0));
+    }
+}
+
+#[test]
+fn test_rabbit_par_blocks() {
+    let key = hex!("43009BC001ABE9E933C7E08715749583");
+    let iv = hex!("2717F4D21A56EBA6");
+
+    // processing 16 bytes at a time only generates single blocks
+    let mut expected = [0u8; 1000];
+    let mut rabbit = Rabbit::new(&key.into(), &iv.into());
+    for chunk in expected.chunks_mut(16) {
+        rabbit.apply_keystream(chunk);
+    }
+
+    let mut buf = [0u8; 1000];
+    let mut rabbit = Rabbit::new(&key.into(), &iv.into());
+    rabbit.apply_keystream(&mut buf);
+    assert_eq!(buf, expected);
+    assert_eq!(rabbit.current_pos::<usize>(), buf.len());
+}
+
+// RFC4503 Appendix A. A.2. Testing with IV Setup (page 7)
+#[test]
+fn test_rabbit_keystream_block() {
+    use rabbit::RabbitCore;
+
+    let key = hex!("00000000000000000000000000000000");
+    let iv = hex!("2717F4D21A56EBA6");
+
+    let mut core = RabbitCore::new(&key.into(), &iv.into());
+    assert_eq!(
+        core.keystream_block(),
+        hex!("4D1051A123AFB670BF8D8505C8D85A44")
+    );
+    assert_eq!(
+        core.keystream_block(),
+        hex!("035BC3ACC667AEAE5B2CF44779F2C896")
+    );
+    assert_eq!(
+        core.keystream_block(),
+        hex!("CB5115F034F03D31171CA75F89FCCB9F")
+    );
+}
+
+#[test]
+fn test_rabbit_reinit() {
+    use rabbit::RabbitCore;
+
+    let key = hex!("ACC351DCF162FC3BFE363D2E29132891");
+    let ivs = [
+        hex!("0000000000000000"),
+        hex!("597E26C175F573C3"),
+        hex!("2717F4D21A56EBA6"),
+    ];
+
+    let mut core = RabbitCore::new(&key.into(), &ivs[2].into());
+    core.keystream_block();
+    for iv in ivs {
+        core.reinit(&iv.into());
+        let mut expected = RabbitCore::new(&key.into(), &iv.into());
+        for _ in 0..3 {
+            assert_eq!(core.keystream_block(), expected.keystream_block());
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn test_rabbit_rng_debug_is_redacted() {
+    use rabbit::{rand_core::SeedableRng, RabbitRng};
+
+    let rng = RabbitRng::from_seed([0x42; 16]);
+    assert_eq!(format!("{:?}", rng), "RabbitRng { .. }");
+}
@@ -34,13 +34,24 @@
 //!     [0x45, 0xA0, 0x1F, 0x64, 0x5F, 0xC3, 0x5B, 0x38, 0x35, 0x52, 0x54, 0x4B, 0x9B, 0xF5]
 //! );
 //! ```
+//!
+//! # Timing side channels
+//!
+//! Both the key schedule and the keystream generation of RC4 index the
+//! 256-byte state with secret-dependent values, so they leak information
+//! about the key and the state through cache timing. This is inherent to
+//! the algorithm and can not be avoided without a substantial slowdown, so
+//! this crate does not attempt to. The key is not retained after the key
+//! schedule, and with the `zeroize` feature enabled the state and the
+//! intermediate index of the key schedule are zeroized.
 
-pub use cipher::{self, consts, KeyInit, StreamCipher};
+pub use cipher::{self, consts, KeyInit, KeyIvInit, StreamCipher};
 
 use cipher::{
     array::{Array, ArraySize},
-    Block, BlockSizeUser, KeySizeUser, ParBlocksSizeUser, StreamCipherBackend, StreamCipherClosure,
-    StreamCipherCore, StreamCipherCoreWrapper,
+    Block, BlockSizeUser, InvalidLength, Iv, IvSizeUser, KeySizeUser, ParBlocks, ParBlocksSizeUser,
+    StreamCipherBackend, StreamCipherClosure, StreamCipherCore, StreamCipherCoreWrapper,
+    StreamCipherSeekCore,
 };
 
 use core::marker::PhantomData;
@@ -48,9 +59,23 @@ use core::marker::PhantomData;
 #[cfg(feature = "zeroize")]
 use cipher::zeroize::{Zeroize, ZeroizeOnDrop};
 
+#[cfg(feature = "rng")]
+mod rng;
+
+#[cfg(feature = "rng")]
+pub use rand_core;
+#[cfg(feature = "rng")]
+pub use rng::Rc4Rng;
+
 /// RC4 key type (8–2048 bits/ 1-256 bytes)
 ///
-/// Implemented as an alias for [`Array`].
+/// Implemented as an alias for [`Array`]. Keys must be at least 1 byte long:
+/// an empty key would leave the state as the identity permutation, so
+/// `KeyInit::new` panics for `KeySize = U0`. The key schedule only uses the
+/// first 256 bytes of the key, and `KeyInit::new` ignores any bytes past them.
+///
+/// `KeyInit::new_from_slice` of the RC4 cores checks these bounds at runtime
+/// and returns an error unless `1 <= key.len() <= 256`.
 pub type Key<KeySize> = Array<u8, KeySize>;
 
 type BlockSize = consts::U1;
@@ -58,6 +83,32 @@ type BlockSize = consts::U1;
 /// The RC4 stream cipher initialized with key.
 pub type Rc4<KeySize> = StreamCipherCoreWrapper<Rc4Core<KeySize>>;
 
+/// The RC4 stream cipher initialized with key, supporting seeking.
+///
+/// RC4 can not skip ahead in its keystream, so seeking via
+/// [`StreamCipherSeek`][cipher::StreamCipherSeek] restarts from the state
+/// saved right after the key schedule and runs the keystream generation up
+/// to the target position. The cost of a seek is linear in the target
+/// position, so this is only reasonable for small offsets. Unless seeking is
+/// needed, prefer [`Rc4`], which does not keep the additional state.
+pub type Rc4Seekable<KeySize> = StreamCipherCoreWrapper<Rc4SeekableCore<KeySize>>;
+
+/// RC4 keyed with the concatenation of a key and an IV, `key || iv`.
+///
+/// # ⚠️ Security Warning
+///
+/// This construction is fundamentally broken. Related keys which share all
+/// but a few bytes leak the common part of the key through the first bytes
+/// of the keystream, which is how WEP was broken (Fluhrer, Mantin and
+/// Shamir, 2001). It is only provided to implement generic code expecting
+/// [`KeyIvInit`] for legacy formats which key RC4 this way. Do not use it
+/// for anything else.
+///
+/// Note that WEP itself prepends the IV instead, which corresponds to
+/// `Rc4WithNonce` with the roles of key and IV swapped. Like for [`Rc4`],
+/// only the first 256 bytes of `key || iv` are used.
+pub type Rc4WithNonce<KeySize, IvSize> = StreamCipherCoreWrapper<Rc4WithNonceCore<KeySize, IvSize>>;
+
 /// Core state of the RC4 stream cipher initialized only with key.
 pub struct Rc4Core<KeySize> {
     state: Rc4State,
@@ -82,6 +133,43 @@ where
             key_size: Default::default(),
         }
     }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        check_key_len(key)?;
+        let key = <&Key<KeySize>>::try_from(key).map_err(|_| InvalidLength)?;
+        Ok(Self::new(key))
+    }
+}
+
+impl<KeySize> Rc4Core<KeySize>
+where
+    KeySize: ArraySize,
+{
+    /// Create new RC4-drop\[n\] core, which discards the first `n` bytes of
+    /// keystream after the key schedule.
+    ///
+    /// The initial bytes of the RC4 keystream are strongly biased, so legacy
+    /// protocols commonly drop 256, 768, or 3072 of them. Using `n = 0` is
+    /// equivalent to [`KeyInit::new`].
+    pub fn new_with_drop(key: &Key<KeySize>, n: usize) -> Self {
+        let mut core = Self::new(key);
+        for _ in 0..n {
+            core.state.prga();
+        }
+        core
+    }
+
+    /// Re-run the key schedule with a new key, reusing this core.
+    ///
+    /// The result is identical to a core created with [`KeyInit::new`].
+    pub fn rekey(&mut self, key: &Key<KeySize>) {
+        self.state = Rc4State::new(key);
+    }
+
+    /// Fill `buf` with raw keystream bytes instead of XORing them into data.
+    pub fn write_keystream(&mut self, buf: &mut [u8]) {
+        self.state.prga_batch(buf);
+    }
 }
 
 impl<KeySize> BlockSizeUser for Rc4Core<KeySize> {
@@ -103,6 +191,143 @@ impl<KeySize> StreamCipherCore for Rc4Core<KeySize> {
 #[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
 impl<KeySize> ZeroizeOnDrop for Rc4Core<KeySize> where KeySize: ArraySize {}
 
+/// Core state of the RC4 stream cipher keyed with `key || iv`.
+pub struct Rc4WithNonceCore<KeySize, IvSize> {
+    state: Rc4State,
+
+    sizes: PhantomData<(KeySize, IvSize)>,
+}
+
+impl<KeySize, IvSize> KeySizeUser for Rc4WithNonceCore<KeySize, IvSize>
+where
+    KeySize: ArraySize,
+{
+    type KeySize = KeySize;
+}
+
+impl<KeySize, IvSize> IvSizeUser for Rc4WithNonceCore<KeySize, IvSize>
+where
+    IvSize: ArraySize,
+{
+    type IvSize = IvSize;
+}
+
+impl<KeySize, IvSize> KeyIvInit for Rc4WithNonceCore<KeySize, IvSize>
+where
+    KeySize: ArraySize,
+    IvSize: ArraySize,
+{
+    fn new(key: &Key<KeySize>, iv: &Iv<Self>) -> Self {
+        Self {
+            state: Rc4State::with_iv(key, iv),
+            sizes: Default::default(),
+        }
+    }
+}
+
+impl<KeySize, IvSize> BlockSizeUser for Rc4WithNonceCore<KeySize, IvSize> {
+    type BlockSize = BlockSize;
+}
+
+impl<KeySize, IvSize> StreamCipherCore for Rc4WithNonceCore<KeySize, IvSize> {
+    #[inline(always)]
+    fn remaining_blocks(&self) -> Option<usize> {
+        None
+    }
+
+    fn process_with_backend(&mut self, f: impl StreamCipherClosure<BlockSize = Self::BlockSize>) {
+        f.call(&mut Backend(&mut self.state));
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl<KeySize, IvSize> ZeroizeOnDrop for Rc4WithNonceCore<KeySize, IvSize>
+where
+    KeySize: ArraySize,
+    IvSize: ArraySize,
+{
+}
+
+/// Core state of the seekable RC4 stream cipher.
+pub struct Rc4SeekableCore<KeySize> {
+    /// State right after the key schedule, used for seeking
+    initial: Rc4State,
+    state: Rc4State,
+    /// Number of generated keystream bytes
+    pos: u64,
+
+    key_size: PhantomData<KeySize>,
+}
+
+impl<KeySize> KeySizeUser for Rc4SeekableCore<KeySize>
+where
+    KeySize: ArraySize,
+{
+    type KeySize = KeySize;
+}
+
+impl<KeySize> KeyInit for Rc4SeekableCore<KeySize>
+where
+    KeySize: ArraySize,
+{
+    fn new(key: &Key<KeySize>) -> Self {
+        let state = Rc4State::new(key);
+        Self {
+            initial: state.clone(),
+            state,
+            pos: 0,
+            key_size: Default::default(),
+        }
+    }
+
+    fn new_from_slice(key: &[u8]) -> Result<Self, InvalidLength> {
+        check_key_len(key)?;
+        let key = <&Key<KeySize>>::try_from(key).map_err(|_| InvalidLength)?;
+        Ok(Self::new(key))
+    }
+}
+
+impl<KeySize> BlockSizeUser for Rc4SeekableCore<KeySize> {
+    type BlockSize = BlockSize;
+}
+
+impl<KeySize> StreamCipherCore for Rc4SeekableCore<KeySize> {
+    #[inline(always)]
+    fn remaining_blocks(&self) -> Option<usize> {
+        let rem = u64::MAX - self.pos;
+        rem.try_into().ok()
+    }
+
+    fn process_with_backend(&mut self, f: impl StreamCipherClosure<BlockSize = Self::BlockSize>) {
+        f.call(&mut CountingBackend {
+            inner: &mut Backend(&mut self.state),
+            pos: &mut self.pos,
+        });
+    }
+}
+
+impl<KeySize> StreamCipherSeekCore for Rc4SeekableCore<KeySize> {
+    type Counter = u64;
+
+    #[inline(always)]
+    fn get_block_pos(&self) -> u64 {
+        self.pos
+    }
+
+    fn set_block_pos(&mut self, pos: u64) {
+        self.state = self.initial.clone();
+        for _ in 0..pos {
+            self.state.prga();
+        }
+        self.pos = pos;
+    }
+}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl<KeySize> ZeroizeOnDrop for Rc4SeekableCore<KeySize> where KeySize: ArraySize {}
+
 struct Backend<'a>(&'a mut Rc4State);
 
 impl BlockSizeUser for Backend<'_> {
@@ -110,7 +335,7 @@ impl BlockSizeUser for Backend<'_> {
 }
 
 impl ParBlocksSizeUser for Backend<'_> {
-    type ParBlocksSize = consts::U1;
+    type ParBlocksSize = consts::U16;
 }
 
 impl StreamCipherBackend for Backend<'_> {
@@ -118,6 +343,56 @@ impl StreamCipherBackend for Backend<'_> {
     fn gen_ks_block(&mut self, block: &mut Block<Self>) {
         block[0] = self.0.prga();
     }
+
+    #[inline(always)]
+    fn gen_par_ks_blocks(&mut self, blocks: &mut ParBlocks<Self>) {
+        let mut buf = [0u8; 16];
+        self.0.prga_batch(&mut buf);
+        for (block, b) in blocks.iter_mut().zip(buf) {
+            block[0] = b;
+        }
+    }
+}
+
+/// Maximum key length used by the key schedule, in bytes.
+const MAX_KEY_LEN: usize = 256;
+
+/// Reject empty keys, which would result in the identity permutation, and
+/// keys longer than the key schedule can use.
+fn check_key_len(key: &[u8]) -> Result<(), InvalidLength> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        Err(InvalidLength)
+    } else {
+        Ok(())
+    }
+}
+
+/// Backend which counts the generated keystream bytes.
+struct CountingBackend<'a, 'b> {
+    inner: &'a mut Backend<'b>,
+    pos: &'a mut u64,
+}
+
+impl BlockSizeUser for CountingBackend<'_, '_> {
+    type BlockSize = BlockSize;
+}
+
+impl ParBlocksSizeUser for CountingBackend<'_, '_> {
+    type ParBlocksSize = <Backend<'static> as ParBlocksSizeUser>::ParBlocksSize;
+}
+
+impl StreamCipherBackend for CountingBackend<'_, '_> {
+    #[inline(always)]
+    fn gen_ks_block(&mut self, block: &mut Block<Self>) {
+        self.inner.gen_ks_block(block);
+        *self.pos += 1;
+    }
+
+    #[inline(always)]
+    fn gen_par_ks_blocks(&mut self, blocks: &mut ParBlocks<Self>) {
+        self.inner.gen_par_ks_blocks(blocks);
+        *self.pos += blocks.len() as u64;
+    }
 }
 
 #[derive(Clone)]
@@ -129,6 +404,8 @@ struct Rc4State {
 
 impl Rc4State {
     fn new(key: &[u8]) -> Self {
+        assert!(!key.is_empty(), "RC4 key must not be empty");
+
         let mut state = Self {
             state: [0; 256],
             i: 0,
@@ -140,13 +417,34 @@ impl Rc4State {
         state
     }
 
+    /// Key schedule with the key `key || iv`
+    fn with_iv(key: &[u8], iv: &[u8]) -> Self {
+        assert!(
+            !key.is_empty() || !iv.is_empty(),
+            "RC4 key must not be empty"
+        );
+
+        let mut state = Self {
+            state: [0; 256],
+            i: 0,
+            j: 0,
+        };
+
+        state.ksa_iter(key.iter().chain(iv).cycle());
+
+        state
+    }
+
     fn ksa(&mut self, key: &[u8]) {
+        self.ksa_iter(key.iter().cycle());
+    }
+
+    fn ksa_iter<'a>(&mut self, key_iter: impl Iterator<Item = &'a u8>) {
         self.state.iter_mut().enumerate().for_each(|(i, x)| {
             *x = i as u8;
         });
 
         let i_iter = 0..256usize;
-        let key_iter = key.iter().cycle();
 
         let mut j = 0u8;
 
@@ -155,6 +453,9 @@ impl Rc4State {
 
             self.state.swap(i, j.into());
         });
+
+        #[cfg(feature = "zeroize")]
+        j.zeroize();
     }
 
     fn s_i(&self) -> u8 {
@@ -175,6 +476,28 @@ impl Rc4State {
 
         self.state[index]
     }
+
+    /// Fill `out` with keystream bytes, equivalent to calling `prga` for
+    /// every byte, but keeping `i` and `j` in locals for the whole loop.
+    fn prga_batch(&mut self, out: &mut [u8]) {
+        let mut i = self.i;
+        let mut j = self.j;
+
+        for b in out {
+            i = i.wrapping_add(1);
+            let s_i = self.state[usize::from(i)];
+            j = j.wrapping_add(s_i);
+            let s_j = self.state[usize::from(j)];
+
+            self.state[usize::from(i)] = s_j;
+            self.state[usize::from(j)] = s_i;
+
+            *b = self.state[usize::from(s_i.wrapping_add(s_j))];
+        }
+
+        self.i = i;
+        self.j = j;
+    }
 }
 
 #[cfg(feature = "zeroize")]
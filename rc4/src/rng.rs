@@ -0,0 +1,149 @@
+//! Random number generator based on the RC4 stream cipher.
+
+use core::fmt::Debug;
+
+use cipher::InvalidLength;
+use rand_core::{impl_try_rng_from_rng_core, RngCore, SeedableRng};
+
+#[cfg(feature = "zeroize")]
+use cipher::zeroize::ZeroizeOnDrop;
+
+use crate::{check_key_len, Rc4State};
+
+/// A random number generator that outputs the RC4 keystream.
+///
+/// # ⚠️ Not cryptographically secure
+///
+/// RC4 is cryptographically broken: its output is biased and distinguishable
+/// from random. This type intentionally does **not** implement
+/// [`CryptoRng`][rand_core::CryptoRng], and must never be used to generate
+/// keys, nonces, or any other secret or security-relevant values. It is only
+/// provided to reproduce the output of legacy tooling and test harnesses
+/// which use RC4 as a fast PRNG.
+///
+/// The seed of [`SeedableRng`] is used as a 256-bit RC4 key. Keys of any
+/// other length can be used with [`Rc4Rng::from_key`].
+///
+/// # Example
+///
+/// ```rust
+/// use rc4::Rc4Rng;
+/// // use rand_core traits
+/// use rc4::rand_core::{RngCore, SeedableRng};
+///
+/// let mut rng = Rc4Rng::from_seed([42u8; 32]);
+/// let x = rng.next_u32();
+/// let mut array = [0u8; 32];
+/// rng.fill_bytes(&mut array);
+/// ```
+#[derive(Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "rng")))]
+pub struct Rc4Rng {
+    state: Rc4State,
+}
+
+impl Rc4Rng {
+    /// Create an RNG outputting the keystream of the RC4 key `key`.
+    ///
+    /// Returns [`InvalidLength`] if the key is empty or longer than 256 bytes.
+    pub fn from_key(key: &[u8]) -> Result<Self, InvalidLength> {
+        check_key_len(key)?;
+        Ok(Self {
+            state: Rc4State::new(key),
+        })
+    }
+}
+
+impl SeedableRng for Rc4Rng {
+    type Seed = [u8; 32];
+
+    #[inline]
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self {
+            state: Rc4State::new(&seed),
+        }
+    }
+}
+
+impl RngCore for Rc4Rng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let mut buf = [0u8; 4];
+        self.state.prga_batch(&mut buf);
+        u32::from_le_bytes(buf)
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        let mut buf = [0u8; 8];
+        self.state.prga_batch(&mut buf);
+        u64::from_le_bytes(buf)
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.state.prga_batch(dest);
+    }
+}
+
+impl_try_rng_from_rng_core!(Rc4Rng);
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl ZeroizeOnDrop for Rc4Rng {}
+
+// Custom Debug implementation that does not expose the internal state
+impl Debug for Rc4Rng {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("Rc4Rng { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+    use rand_core::TryRngCore;
+
+    // RFC 6229, 40-bit key 0x0102030405
+    const KEY: [u8; 5] = hex!("0102030405");
+    const KEYSTREAM: [u8; 32] =
+        hex!("b2396305f03dc027ccc3524a0a1118a86982944f18fc82d589c403a47a0d0919");
+
+    #[test]
+    fn test_rc4_rng_fill_bytes() {
+        let mut rng = Rc4Rng::from_key(&KEY).unwrap();
+        let mut buf = [0u8; 32];
+        rng.fill_bytes(&mut buf[..3]);
+        rng.fill_bytes(&mut buf[3..]);
+        assert_eq!(buf, KEYSTREAM);
+    }
+
+    #[test]
+    fn test_rc4_rng_next_words() {
+        let mut rng = Rc4Rng::from_key(&KEY).unwrap();
+        assert_eq!(rng.next_u32().to_le_bytes(), KEYSTREAM[..4]);
+        assert_eq!(rng.next_u64().to_le_bytes(), KEYSTREAM[4..12]);
+        assert_eq!(rng.try_next_u32().unwrap().to_le_bytes(), KEYSTREAM[12..16]);
+    }
+
+    #[test]
+    fn test_rc4_rng_from_seed() {
+        // RFC 6229, 256-bit key 0x0102...1f20
+        let seed = hex!("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20");
+        let mut rng = Rc4Rng::from_seed(seed);
+        let mut buf = [0u8; 32];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(
+            buf,
+            hex!("eaa6bd25880bf93d3f5d1e4ca2611d91cfa45c9f7e714b54bdfa80027cb14380")
+        );
+    }
+
+    #[test]
+    fn test_rc4_rng_from_key_length() {
+        assert!(Rc4Rng::from_key(&[]).is_err());
+        assert!(Rc4Rng::from_key(&[0x42; 256]).is_ok());
+        assert!(Rc4Rng::from_key(&[0x42; 257]).is_err());
+    }
+}
@@ -0,0 +1,7 @@
+//! Property tests checking that encrypting in arbitrary chunks matches
+//! encrypting in a single call.
+
+use rc4::{consts::U16, Rc4};
+use stream_ciphers_test_helpers::chunked_test;
+
+chunked_test!(rc4_chunked, Rc4<U16>, key: 16);
@@ -3,8 +3,8 @@
 #![allow(deprecated)] // uses `from_slice`
 
 use hex_literal::hex;
-use rc4::{consts::*, KeyInit, StreamCipher};
-use rc4::{Key, Rc4};
+use rc4::{cipher::StreamCipherSeek, consts::*, KeyInit, StreamCipher};
+use rc4::{Key, Rc4, Rc4Core, Rc4Seekable};
 
 #[test]
 fn test_rfc6229_length_40_bits_key1() {
@@ -597,3 +597,144 @@ fn test_rfc6229_length_256_bits_key2() {
         assert_eq!(data[offset..offset + 16], chunk[2..]);
     }
 }
+
+#[test]
+fn test_rc4_drop() {
+    let key = Key::<U5>::from_slice(&hex!("0102030405"));
+
+    let mut data = [0u8; 16];
+    Rc4::<_>::from_core(Rc4Core::new_with_drop(key, 0)).apply_keystream(&mut data);
+    assert_eq!(data, hex!("b2396305f03dc027ccc3524a0a1118a8"));
+
+    // RFC 6229 keystream at offset 256
+    let mut data = [0u8; 16];
+    Rc4::<_>::from_core(Rc4Core::new_with_drop(key, 256)).apply_keystream(&mut data);
+    assert_eq!(data, hex!("1cfcf62b03eddb641d77dfcf7f8d8c93"));
+}
+
+#[test]
+fn test_rc4_split() {
+    let key = Key::<U16>::from_slice(&hex!("0102030405060708090a0b0c0d0e0f10"));
+
+    // processing one byte at a time only generates single blocks
+    let mut expected = [0u8; 1000];
+    let mut cipher = Rc4::<_>::new(key);
+    for b in expected.chunks_mut(1) {
+        cipher.apply_keystream(b);
+    }
+
+    for n in [7, 16, 17, 100, 1000] {
+        let mut data = [0u8; 1000];
+        let mut cipher = Rc4::<_>::new(key);
+        for chunk in data.chunks_mut(n) {
+            cipher.apply_keystream(chunk);
+        }
+        assert_eq!(data, expected, "chunk size {n}");
+    }
+}
+
+#[test]
+fn test_rc4_rekey() {
+    let key1 = Key::<U5>::from_slice(&hex!("0102030405"));
+    let key2 = Key::<U5>::from_slice(&hex!("833222772a"));
+
+    let mut core = Rc4Core::new_with_drop(key1, 100);
+    core.rekey(key2);
+
+    let mut data = [0u8; 32];
+    Rc4::<_>::from_core(core).apply_keystream(&mut data);
+    assert_eq!(
+        data,
+        hex!("80ad97bdc973df8a2e879e92a497efda20f060c2f2e5126501d3d4fea10d5fc0")
+    );
+}
+
+#[test]
+fn test_rc4_write_keystream() {
+    let key = Key::<U5>::from_slice(&hex!("0102030405"));
+    let mut core = Rc4Core::new(key);
+
+    let mut keystream = [0u8; 0x110];
+    core.write_keystream(&mut keystream[..7]);
+    core.write_keystream(&mut keystream[7..]);
+    assert_eq!(
+        keystream[..0x20],
+        hex!("b2396305f03dc027ccc3524a0a1118a86982944f18fc82d589c403a47a0d0919")
+    );
+    assert_eq!(keystream[0x100..], hex!("1cfcf62b03eddb641d77dfcf7f8d8c93"));
+}
+
+#[test]
+fn test_rc4_seek() {
+    let key = Key::<U5>::from_slice(&hex!("0102030405"));
+
+    let mut expected = [0u8; 1000];
+    Rc4::<_>::new(key).apply_keystream(&mut expected);
+
+    let mut cipher = Rc4Seekable::<_>::new(key);
+    for pos in [0, 1, 15, 16, 17, 500, 100, 999] {
+        cipher.seek(pos);
+        assert_eq!(cipher.current_pos::<usize>(), pos);
+
+        let mut data = [0u8; 1000];
+        cipher.apply_keystream(&mut data[pos..]);
+        assert_eq!(data[pos..], expected[pos..]);
+        assert_eq!(cipher.current_pos::<usize>(), data.len());
+    }
+}
+
+#[test]
+fn test_rc4_empty_key() {
+    use rc4::Rc4SeekableCore;
+
+    assert!(Rc4Core::<U0>::new_from_slice(&[]).is_err());
+    assert!(Rc4SeekableCore::<U0>::new_from_slice(&[]).is_err());
+    assert!(Rc4Core::<U1>::new_from_slice(&[]).is_err());
+    assert!(Rc4Core::<U1>::new_from_slice(&[1]).is_ok());
+}
+
+#[test]
+fn test_rc4_with_nonce() {
+    use rc4::{KeyIvInit, Rc4WithNonce};
+
+    // `key || iv` is the 40-bit key of the first RFC 6229 vector
+    let key = hex!("0102");
+    let iv = hex!("030405");
+    let mut cipher = Rc4WithNonce::<U2, U3>::new(&key.into(), &iv.into());
+
+    let mut data = [0u8; 32];
+    cipher.apply_keystream(&mut data);
+    assert_eq!(
+        data,
+        hex!(
+            "b2396305f03dc027ccc3524a0a1118a8"
+            "6982944f18fc82d589c403a47a0d0919"
+        )
+    );
+}
+
+#[test]
+fn test_rc4_key_len_bounds() {
+    use rc4::Rc4SeekableCore;
+
+    assert!(Rc4Core::<U256>::new_from_slice(&[0x42; 256]).is_ok());
+    assert!(Rc4SeekableCore::<U256>::new_from_slice(&[0x42; 256]).is_ok());
+    assert!(Rc4Core::<U257>::new_from_slice(&[0x42; 257]).is_err());
+    assert!(Rc4SeekableCore::<U257>::new_from_slice(&[0x42; 257]).is_err());
+    assert!(Rc4Core::<U256>::new_from_slice(&[0x42; 255]).is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_rc4_empty_key_panics() {
+    Rc4::<U0>::new(&Key::default());
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn test_rc4_rng_debug_is_redacted() {
+    use rc4::{rand_core::SeedableRng, Rc4Rng};
+
+    let rng = Rc4Rng::from_seed([0x42; 32]);
+    assert_eq!(format!("{:?}", rng), "Rc4Rng { .. }");
+}
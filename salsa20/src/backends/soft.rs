@@ -3,31 +3,50 @@
 
 use crate::{Block, SalsaCore, Unsigned, STATE_WORDS};
 use cipher::{
-    consts::{U1, U64},
-    BlockSizeUser, ParBlocksSizeUser, StreamCipherBackend, StreamCipherSeekCore,
+    consts::{U4, U64},
+    BlockSizeUser, ParBlocks, ParBlocksSizeUser, StreamCipherBackend, StreamCipherSeekCore,
 };
 
-pub(crate) struct Backend<'a, R: Unsigned>(pub(crate) &'a mut SalsaCore<R>);
+pub(crate) struct Backend<'a, R: Unsigned, KeySize>(pub(crate) &'a mut SalsaCore<R, KeySize>);
 
-impl<R: Unsigned> BlockSizeUser for Backend<'_, R> {
+impl<R: Unsigned, KeySize> BlockSizeUser for Backend<'_, R, KeySize> {
     type BlockSize = U64;
 }
 
-impl<R: Unsigned> ParBlocksSizeUser for Backend<'_, R> {
-    type ParBlocksSize = U1;
+impl<R: Unsigned, KeySize> ParBlocksSizeUser for Backend<'_, R, KeySize> {
+    type ParBlocksSize = U4;
 }
 
-impl<R: Unsigned> StreamCipherBackend for Backend<'_, R> {
+impl<R: Unsigned, KeySize> StreamCipherBackend for Backend<'_, R, KeySize> {
     #[inline(always)]
     fn gen_ks_block(&mut self, block: &mut Block<Self>) {
         let res = run_rounds::<R>(&self.0.state);
 
-        self.0.set_block_pos(self.0.get_block_pos() + 1);
+        self.0.set_block_pos(self.0.get_block_pos().wrapping_add(1));
 
         for (chunk, val) in block.chunks_exact_mut(4).zip(res.iter()) {
             chunk.copy_from_slice(&val.to_le_bytes());
         }
     }
+
+    #[inline(always)]
+    fn gen_par_ks_blocks(&mut self, blocks: &mut ParBlocks<Self>) {
+        let pos = self.0.get_block_pos();
+
+        for (i, block) in blocks.iter_mut().enumerate() {
+            let mut state = self.0.state;
+            let block_pos = pos.wrapping_add(i as u64);
+            state[8] = block_pos as u32;
+            state[9] = (block_pos >> 32) as u32;
+
+            let res = run_rounds::<R>(&state);
+            for (chunk, val) in block.chunks_exact_mut(4).zip(res.iter()) {
+                chunk.copy_from_slice(&val.to_le_bytes());
+            }
+        }
+
+        self.0.set_block_pos(pos.wrapping_add(blocks.len() as u64));
+    }
 }
 
 #[inline]
@@ -46,25 +65,70 @@ pub(crate) fn quarter_round(
 }
 
 #[inline(always)]
-fn run_rounds<R: Unsigned>(state: &[u32; STATE_WORDS]) -> [u32; STATE_WORDS] {
-    let mut res = *state;
-
-    for _ in 0..R::USIZE {
+pub(crate) fn double_rounds(state: &mut [u32; STATE_WORDS], count: usize) {
+    for _ in 0..count {
         // column rounds
-        quarter_round(0, 4, 8, 12, &mut res);
-        quarter_round(5, 9, 13, 1, &mut res);
-        quarter_round(10, 14, 2, 6, &mut res);
-        quarter_round(15, 3, 7, 11, &mut res);
+        quarter_round(0, 4, 8, 12, state);
+        quarter_round(5, 9, 13, 1, state);
+        quarter_round(10, 14, 2, 6, state);
+        quarter_round(15, 3, 7, 11, state);
 
         // diagonal rounds
-        quarter_round(0, 1, 2, 3, &mut res);
-        quarter_round(5, 6, 7, 4, &mut res);
-        quarter_round(10, 11, 8, 9, &mut res);
-        quarter_round(15, 12, 13, 14, &mut res);
+        quarter_round(0, 1, 2, 3, state);
+        quarter_round(5, 6, 7, 4, state);
+        quarter_round(10, 11, 8, 9, state);
+        quarter_round(15, 12, 13, 14, state);
     }
+}
+
+#[inline(always)]
+fn run_rounds<R: Unsigned>(state: &[u32; STATE_WORDS]) -> [u32; STATE_WORDS] {
+    let mut res = *state;
+
+    double_rounds(&mut res, R::USIZE);
 
     for (s1, s0) in res.iter_mut().zip(state.iter()) {
         *s1 = s1.wrapping_add(*s0);
     }
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Backend;
+    use crate::SalsaCore;
+    use cipher::{
+        consts::{U10, U32, U4},
+        KeyIvInit, ParBlocks, StreamCipherBackend, StreamCipherSeekCore,
+    };
+
+    const KEY: [u8; 32] = [0x42; 32];
+    const NONCE: [u8; 8] = [0x24; 8];
+
+    fn check<R: crate::Unsigned>(start: u64) {
+        let mut core = SalsaCore::<R>::new(&KEY.into(), &NONCE.into());
+        core.set_block_pos(start);
+        let mut expected = ParBlocks::<Backend<'_, R, U32>>::default();
+        for block in expected.iter_mut() {
+            Backend(&mut core).gen_ks_block(block);
+        }
+        let expected_pos = core.get_block_pos();
+
+        let mut core = SalsaCore::<R>::new(&KEY.into(), &NONCE.into());
+        core.set_block_pos(start);
+        let mut blocks = ParBlocks::<Backend<'_, R, U32>>::default();
+        Backend(&mut core).gen_par_ks_blocks(&mut blocks);
+
+        assert_eq!(blocks, expected, "start {start}");
+        assert_eq!(core.get_block_pos(), expected_pos);
+    }
+
+    #[test]
+    fn par_blocks_match_single_blocks() {
+        // the second start value exercises the carry into the high counter word
+        for start in [0, u64::from(u32::MAX) - 1] {
+            check::<U10>(start);
+            check::<U4>(start);
+        }
+    }
+}
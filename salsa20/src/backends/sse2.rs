@@ -1,7 +1,4 @@
-use crate::{
-    backends::soft::Backend as SoftBackend, Block, SalsaCore, StreamCipherClosure, Unsigned,
-    STATE_WORDS,
-};
+use crate::{Block, StreamCipherClosure, Unsigned, STATE_WORDS};
 use cipher::{
     consts::{U1, U64},
     BlockSizeUser, ParBlocksSizeUser, StreamCipherBackend,
@@ -20,7 +17,16 @@ where
     R: Unsigned,
     F: StreamCipherClosure<BlockSize = U64>,
 {
-    let state_ptr = state.as_ptr() as *const __m128i;
+    // Arrange the state words by diagonals, so that every quarter round of
+    // a column (or row) round operates on a single lane of the vectors
+    #[rustfmt::skip]
+    let diagonals = [
+        state[0], state[5], state[10], state[15],
+        state[4], state[9], state[14], state[3],
+        state[8], state[13], state[2], state[7],
+        state[12], state[1], state[6], state[11],
+    ];
+    let state_ptr = diagonals.as_ptr() as *const __m128i;
     let mut backend = Backend::<R> {
         v: [
             _mm_loadu_si128(state_ptr.add(0)),
@@ -31,16 +37,12 @@ where
         _pd: PhantomData,
     };
 
-    // The SSE2 backend only works for Salsa20/20. Any other variant will fallback to the soft backend.
-    if R::USIZE == 10 {
-        f.call(&mut backend);
-        state[8] = _mm_cvtsi128_si32(backend.v[2]) as u32;
-    } else {
-        f.call(&mut SoftBackend(&mut SalsaCore::<R> {
-            state: *state,
-            rounds: PhantomData,
-        }));
-    }
+    f.call(&mut backend);
+
+    // the block counter is stored in the first lane of `v[2]` (low word)
+    // and the second lane of `v[1]` (high word)
+    state[8] = _mm_cvtsi128_si32(backend.v[2]) as u32;
+    state[9] = _mm_cvtsi128_si32(_mm_shuffle_epi32(backend.v[1], 0b_00_00_00_01)) as u32;
 }
 
 struct Backend<R: Unsigned> {
@@ -63,6 +65,9 @@ impl<R: Unsigned> StreamCipherBackend for Backend<R> {
             let res = rounds::<R>(&self.v);
 
             self.v[2] = _mm_add_epi32(self.v[2], _mm_set_epi32(0, 0, 0, 1));
+            if _mm_cvtsi128_si32(self.v[2]) == 0 {
+                self.v[1] = _mm_add_epi32(self.v[1], _mm_set_epi32(0, 0, 1, 0));
+            }
             let block_ptr = block.as_mut_ptr() as *mut __m128i;
 
             for (i, v) in res.iter().enumerate() {
@@ -164,3 +169,37 @@ unsafe fn transpose([a, b, c, d]: &mut [__m128i; 4]) {
     *c = _mm_unpacklo_epi64(t2, t3);
     *d = _mm_unpackhi_epi64(t2, t3);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{backends::soft, Salsa20, SalsaCore};
+    use cipher::{consts::U10, KeyIvInit, StreamCipher, StreamCipherBackend};
+
+    const KEY: [u8; 32] = [0x42; 32];
+    const NONCE: [u8; 8] = [0x24; 8];
+
+    #[test]
+    fn sse2_matches_soft() {
+        let mut expected = [0u8; 1024];
+        let mut core = SalsaCore::<U10>::new(&KEY.into(), &NONCE.into());
+        let mut backend = soft::Backend(&mut core);
+        for chunk in expected.chunks_exact_mut(64) {
+            backend.gen_ks_block(chunk.try_into().unwrap());
+        }
+
+        for len in [1, 63, 64, 65, 255, 256, 257, 1000] {
+            let mut buf = [0u8; 1000];
+            let mut cipher = Salsa20::new(&KEY.into(), &NONCE.into());
+            cipher.apply_keystream(&mut buf[..len]);
+            assert_eq!(buf[..len], expected[..len], "length {len}");
+
+            // split the buffer to exercise the buffered tail of the previous call
+            let mut buf = [0u8; 1000];
+            let mut cipher = Salsa20::new(&KEY.into(), &NONCE.into());
+            let (head, tail) = buf[..len].split_at_mut(len / 2);
+            cipher.apply_keystream(head);
+            cipher.apply_keystream(tail);
+            assert_eq!(buf[..len], expected[..len], "split length {len}");
+        }
+    }
+}
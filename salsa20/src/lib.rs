@@ -61,6 +61,25 @@
 //! assert_eq!(buffer, ciphertext);
 //! ```
 //!
+//! Raw keystream can be obtained by applying it to a zeroed buffer, or in whole
+//! blocks by using [`SalsaCore`] directly through the
+//! [`StreamCipherCore`] trait:
+//!
+//! ```
+//! use salsa20::{Salsa20, SalsaCore};
+//! use salsa20::cipher::{consts::U10, KeyIvInit, StreamCipher, StreamCipherCore};
+//!
+//! let key = [0x42; 32];
+//! let nonce = [0x24; 8];
+//!
+//! let mut keystream = [0u8; 64];
+//! Salsa20::new(&key.into(), &nonce.into()).apply_keystream(&mut keystream);
+//!
+//! let mut block = Default::default();
+//! SalsaCore::<U10>::new(&key.into(), &nonce.into()).write_keystream_block(&mut block);
+//! assert_eq!(block[..], keystream);
+//! ```
+//!
 //! Salsa20 will run the SSE2 backend in x86(-64) targets for Salsa20/20 variant.
 //! Other variants will fallback to the software backend.
 //!
@@ -79,9 +98,10 @@ pub use cipher;
 
 use cipher::{
     array::{typenum::Unsigned, Array},
-    consts::{U10, U24, U32, U4, U6, U64, U8},
-    Block, BlockSizeUser, IvSizeUser, KeyIvInit, KeySizeUser, StreamCipherClosure,
-    StreamCipherCore, StreamCipherCoreWrapper, StreamCipherSeekCore,
+    consts::{U10, U16, U24, U32, U4, U6, U64, U8},
+    Block, BlockSizeUser, Iv, IvSizeUser, KeyIvInit, KeySizeUser, StreamCipher,
+    StreamCipherClosure, StreamCipherCore, StreamCipherCoreWrapper, StreamCipherSeek,
+    StreamCipherSeekCore,
 };
 use core::marker::PhantomData;
 
@@ -89,8 +109,20 @@ use core::marker::PhantomData;
 use cipher::zeroize::{Zeroize, ZeroizeOnDrop};
 
 mod backends;
+#[cfg(feature = "rng")]
+mod rng;
 mod xsalsa;
 
+#[cfg(feature = "rng")]
+pub use rand_core;
+#[cfg(feature = "rng")]
+pub use rng::{
+    Salsa12Core, Salsa12Rng, Salsa20Core, Salsa20Rng, Salsa8Core, Salsa8Rng, XSalsa20Rng,
+    XSalsa20Seed,
+};
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use stream_cipher_writer::StreamCipherWriter;
 pub use xsalsa::{hsalsa, XSalsa12, XSalsa20, XSalsa8, XSalsaCore};
 
 /// Salsa20/8 stream cipher
@@ -103,11 +135,34 @@ pub type Salsa12 = StreamCipherCoreWrapper<SalsaCore<U6>>;
 
 /// Salsa20/20 stream cipher
 /// (20 rounds; **recommended**)
+///
+/// The keystream position is tracked by a 64-bit counter of 64-byte blocks.
+/// [`StreamCipherSeek`][cipher::StreamCipherSeek] methods operate on byte
+/// positions, so seeking to block `n` is done with `seek(n * 64)`, and the
+/// current block is `current_pos::<u64>() / 64`.
 pub type Salsa20 = StreamCipherCoreWrapper<SalsaCore<U10>>;
 
+/// Salsa20/8 stream cipher with 128-bit key
+/// (reduced-round variant of Salsa20 with 8 rounds, *not recommended*)
+#[allow(non_camel_case_types)]
+pub type Salsa8_128 = StreamCipherCoreWrapper<SalsaCore<U4, U16>>;
+
+/// Salsa20/12 stream cipher with 128-bit key
+/// (reduced-round variant of Salsa20 with 12 rounds, *not recommended*)
+#[allow(non_camel_case_types)]
+pub type Salsa12_128 = StreamCipherCoreWrapper<SalsaCore<U6, U16>>;
+
+/// Salsa20/20 stream cipher with 128-bit key
+/// (20 rounds; 256-bit keys via [`Salsa20`] are **recommended**)
+#[allow(non_camel_case_types)]
+pub type Salsa20_128 = StreamCipherCoreWrapper<SalsaCore<U10, U16>>;
+
 /// Key type used by all Salsa variants and [`XSalsa20`].
 pub type Key = Array<u8, U32>;
 
+/// Key type used by the 128-bit key Salsa variants, e.g. [`Salsa20_128`].
+pub type Key128 = Array<u8, U16>;
+
 /// Nonce type used by all Salsa variants.
 pub type Nonce = Array<u8, U8>;
 
@@ -120,17 +175,42 @@ const STATE_WORDS: usize = 16;
 /// State initialization constant ("expand 32-byte k")
 const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
 
+/// State initialization constant for 128-bit keys ("expand 16-byte k")
+const CONSTANTS_128: [u32; 4] = [0x6170_7865, 0x3120_646e, 0x7962_2d36, 0x6b20_6574];
+
+/// Apply `double_rounds` Salsa double rounds to `state`.
+///
+/// This is a portable reference implementation of the permutation used by all
+/// Salsa variants (Salsa20/20 performs 10 double rounds), intended for
+/// cross-checking other implementations, e.g. in tests or fuzzers. It never
+/// uses SIMD backends, and the state is expected in the canonical word order.
+///
+/// Note that the input state is not added to the result, i.e. the keystream
+/// block is obtained by adding the original state words to the permuted ones.
+pub fn soft_rounds(state: &mut [u32; STATE_WORDS], double_rounds: usize) {
+    backends::soft::double_rounds(state, double_rounds);
+}
+
 /// The Salsa20 core function.
-pub struct SalsaCore<R: Unsigned> {
+///
+/// `KeySize` selects between the 256-bit key (`U32`, default) and the
+/// 128-bit key (`U16`) variants of Salsa20.
+#[derive(Clone)]
+pub struct SalsaCore<R: Unsigned, KeySize = U32> {
     /// Internal state of the core function
     state: [u32; STATE_WORDS],
-    /// Number of rounds to perform
-    rounds: PhantomData<R>,
+    /// Number of rounds to perform and key size
+    rounds: PhantomData<(R, KeySize)>,
 }
 
-impl<R: Unsigned> SalsaCore<R> {
+impl<R: Unsigned, KeySize> SalsaCore<R, KeySize> {
     /// Create new Salsa core from raw state.
     ///
+    /// The state must be in the canonical Salsa20 word order on every target:
+    /// words 8 and 9 hold the low and high halves of the block counter. (On
+    /// x86 targets the state was previously expected in the diagonal order
+    /// used by the SSE2 backend.)
+    ///
     /// This method is mainly intended for the `scrypt` crate.
     /// Other users generally should not use this method.
     pub fn from_raw_state(state: [u32; STATE_WORDS]) -> Self {
@@ -145,58 +225,110 @@ impl<R: Unsigned> KeySizeUser for SalsaCore<R> {
     type KeySize = U32;
 }
 
-impl<R: Unsigned> IvSizeUser for SalsaCore<R> {
+impl<R: Unsigned> KeySizeUser for SalsaCore<R, U16> {
+    type KeySize = U16;
+}
+
+impl<R: Unsigned, KeySize> IvSizeUser for SalsaCore<R, KeySize> {
     type IvSize = U8;
 }
 
-impl<R: Unsigned> BlockSizeUser for SalsaCore<R> {
+impl<R: Unsigned, KeySize> BlockSizeUser for SalsaCore<R, KeySize> {
     type BlockSize = U64;
 }
 
 impl<R: Unsigned> KeyIvInit for SalsaCore<R> {
     fn new(key: &Key, iv: &Nonce) -> Self {
-        let mut state = [0u32; STATE_WORDS];
-        state[0] = CONSTANTS[0];
+        Self::from_raw_state(init_state(&CONSTANTS, &key[..16], &key[16..], iv))
+    }
+}
 
-        for (i, chunk) in key[..16].chunks(4).enumerate() {
-            state[1 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
-        }
+impl<R: Unsigned> KeyIvInit for SalsaCore<R, U16> {
+    /// The 128-bit key is used for both key halves of the state.
+    fn new(key: &Key128, iv: &Nonce) -> Self {
+        Self::from_raw_state(init_state(&CONSTANTS_128, key, key, iv))
+    }
+}
 
-        state[5] = CONSTANTS[1];
+impl<R: Unsigned, KeySize> SalsaCore<R, KeySize>
+where
+    Self: KeyIvInit,
+{
+    /// Create a new core with the block counter set to `counter`.
+    ///
+    /// This is equivalent to [`KeyIvInit::new`] followed by seeking to the
+    /// block `counter`, i.e. to byte offset `64 * counter`.
+    pub fn new_with_counter(key: &cipher::Key<Self>, iv: &Iv<Self>, counter: u64) -> Self {
+        let mut core = Self::new(key, iv);
+        core.set_block_pos(counter);
+        core
+    }
 
-        for (i, chunk) in iv.chunks(4).enumerate() {
-            state[6 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
-        }
+    /// Write the keystream bytes in `[start, start + buf.len())` into `buf`.
+    ///
+    /// This is a stateless alternative to seeking a shared cipher instance,
+    /// e.g. for concurrent random-access reads of an encrypted blob. A temporary
+    /// cipher is created from `key` and `iv` on every call, so the key setup is
+    /// repeated each time.
+    ///
+    /// ```
+    /// use salsa20::{Salsa20, SalsaCore};
+    /// use salsa20::cipher::{consts::U10, KeyIvInit, StreamCipher};
+    ///
+    /// let key = [0x42; 32].into();
+    /// let nonce = [0x24; 8].into();
+    ///
+    /// let mut keystream = [0u8; 100];
+    /// Salsa20::new(&key, &nonce).apply_keystream(&mut keystream);
+    ///
+    /// let mut range = [0u8; 30];
+    /// SalsaCore::<U10>::keystream_range(&key, &nonce, 50, &mut range);
+    /// assert_eq!(range, keystream[50..80]);
+    /// ```
+    ///
+    /// # Panics
+    /// If the range extends past the end of the keystream.
+    pub fn keystream_range(key: &cipher::Key<Self>, iv: &Iv<Self>, start: u64, buf: &mut [u8]) {
+        let mut cipher = StreamCipherCoreWrapper::from_core(Self::new(key, iv));
+        cipher.seek(start);
+        buf.fill(0);
+        cipher.apply_keystream(buf);
+    }
+}
 
-        state[8] = 0;
-        state[9] = 0;
-        state[10] = CONSTANTS[2];
+/// Build the initial state with the block counter set to zero.
+fn init_state(
+    constants: &[u32; 4],
+    key_lo: &[u8],
+    key_hi: &[u8],
+    iv: &Nonce,
+) -> [u32; STATE_WORDS] {
+    let mut state = [0u32; STATE_WORDS];
+    state[0] = constants[0];
+
+    for (i, chunk) in key_lo.chunks(4).enumerate() {
+        state[1 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
 
-        for (i, chunk) in key[16..].chunks(4).enumerate() {
-            state[11 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
-        }
+    state[5] = constants[1];
 
-        state[15] = CONSTANTS[3];
+    for (i, chunk) in iv.chunks(4).enumerate() {
+        state[6 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
 
-        cfg_if! {
-            if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
-                state = [
-                    state[0], state[5], state[10], state[15],
-                    state[4], state[9], state[14], state[3],
-                    state[8], state[13], state[2], state[7],
-                    state[12], state[1], state[6], state[11],
-                ];
-            }
-        }
+    state[8] = 0;
+    state[9] = 0;
+    state[10] = constants[2];
 
-        Self {
-            state,
-            rounds: PhantomData,
-        }
+    for (i, chunk) in key_hi.chunks(4).enumerate() {
+        state[11 + i] = u32::from_le_bytes(chunk.try_into().unwrap());
     }
+
+    state[15] = constants[3];
+    state
 }
 
-impl<R: Unsigned> StreamCipherCore for SalsaCore<R> {
+impl<R: Unsigned, KeySize> StreamCipherCore for SalsaCore<R, KeySize> {
     #[inline(always)]
     fn remaining_blocks(&self) -> Option<usize> {
         let rem = u64::MAX - self.get_block_pos();
@@ -205,8 +337,14 @@ impl<R: Unsigned> StreamCipherCore for SalsaCore<R> {
     fn process_with_backend(&mut self, f: impl StreamCipherClosure<BlockSize = Self::BlockSize>) {
         cfg_if! {
             if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
-                unsafe {
-                    backends::sse2::inner::<R, _>(&mut self.state, f);
+                // The SSE2 backend only works for Salsa20/20.
+                // Any other variant will fallback to the soft backend.
+                if R::USIZE == 10 {
+                    unsafe {
+                        backends::sse2::inner::<R, _>(&mut self.state, f);
+                    }
+                } else {
+                    f.call(&mut backends::soft::Backend(self));
                 }
             } else {
                 f.call(&mut backends::soft::Backend(self));
@@ -215,39 +353,59 @@ impl<R: Unsigned> StreamCipherCore for SalsaCore<R> {
     }
 }
 
-impl<R: Unsigned> StreamCipherSeekCore for SalsaCore<R> {
+impl<R: Unsigned, KeySize> StreamCipherSeekCore for SalsaCore<R, KeySize> {
     type Counter = u64;
 
     #[inline(always)]
     fn get_block_pos(&self) -> u64 {
-        cfg_if! {
-            if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
-                (self.state[8] as u64) + ((self.state[5] as u64) << 32)
-            }
-            else {
-                (self.state[8] as u64) + ((self.state[9] as u64) << 32)
-            }
-        }
+        (self.state[8] as u64) + ((self.state[9] as u64) << 32)
     }
 
     #[inline(always)]
     fn set_block_pos(&mut self, pos: u64) {
-        cfg_if! {
-            if #[cfg(any(target_arch = "x86", target_arch = "x86_64"))] {
-                self.state[8] = (pos & 0xffff_ffff) as u32;
-                self.state[5] = ((pos >> 32) & 0xffff_ffff) as u32;
-            }
-            else {
-                self.state[8] = (pos & 0xffff_ffff) as u32;
-                self.state[9] = ((pos >> 32) & 0xffff_ffff) as u32;
-            }
+        self.state[8] = (pos & 0xffff_ffff) as u32;
+        self.state[9] = ((pos >> 32) & 0xffff_ffff) as u32;
+    }
+}
+
+/// Extract the core from a cipher, e.g. to inspect its state or to reuse it
+/// the way `scrypt` does. Use [`StreamCipherCoreWrapper::from_core`] to
+/// convert it back.
+///
+/// The wrapper buffers the unused keystream of a partially consumed block,
+/// which the core does not hold, so the conversion fails with
+/// [`PartialBlockError`] unless the cipher is at a block boundary.
+impl<R: Unsigned, KeySize: Clone> TryFrom<StreamCipherCoreWrapper<SalsaCore<R, KeySize>>>
+    for SalsaCore<R, KeySize>
+{
+    type Error = PartialBlockError;
+
+    fn try_from(
+        cipher: StreamCipherCoreWrapper<SalsaCore<R, KeySize>>,
+    ) -> Result<Self, Self::Error> {
+        if cipher.current_pos::<u128>() % 64 != 0 {
+            return Err(PartialBlockError);
         }
+        Ok(cipher.get_core().clone())
+    }
+}
+
+/// Error returned when converting a cipher into its core in the middle of
+/// a block.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PartialBlockError;
+
+impl core::fmt::Display for PartialBlockError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("cipher is not at a block boundary")
     }
 }
 
+impl core::error::Error for PartialBlockError {}
+
 #[cfg(feature = "zeroize")]
 #[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
-impl<R: Unsigned> Drop for SalsaCore<R> {
+impl<R: Unsigned, KeySize> Drop for SalsaCore<R, KeySize> {
     fn drop(&mut self) {
         self.state.zeroize();
     }
@@ -255,4 +413,4 @@ impl<R: Unsigned> Drop for SalsaCore<R> {
 
 #[cfg(feature = "zeroize")]
 #[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
-impl<R: Unsigned> ZeroizeOnDrop for SalsaCore<R> {}
+impl<R: Unsigned, KeySize> ZeroizeOnDrop for SalsaCore<R, KeySize> {}
@@ -0,0 +1,614 @@
+//! Random number generators based on the Salsa family of stream ciphers.
+
+use core::fmt::Debug;
+
+use cipher::{
+    consts::{U10, U4, U6},
+    Block, KeyIvInit, StreamCipherCore, StreamCipherSeekCore,
+};
+use rand_core::{
+    block::{BlockRng, BlockRngCore, CryptoBlockRng},
+    impl_try_rng_from_rng_core, CryptoRng, RngCore, SeedableRng,
+};
+
+#[cfg(feature = "zeroize")]
+use cipher::zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::{hsalsa, Nonce, SalsaCore, Unsigned};
+
+// number of 32-bit words per Salsa block (fixed by algorithm definition)
+const BLOCK_WORDS: usize = 16;
+
+const BUFFER_SIZE: usize = 64;
+
+// NB. this must remain consistent with `BUFFER_SIZE`
+const BUF_BLOCKS: usize = BUFFER_SIZE / BLOCK_WORDS;
+
+// word positions are 68-bit numbers: a 64-bit block counter and a 4-bit word index
+const WORD_POS_MASK: u128 = (1 << 68) - 1;
+
+/// The results buffer that zeroizes on drop when the `zeroize` feature is enabled.
+#[derive(Clone)]
+pub struct BlockRngResults([u32; BUFFER_SIZE]);
+
+impl AsRef<[u32]> for BlockRngResults {
+    fn as_ref(&self) -> &[u32] {
+        &self.0
+    }
+}
+
+impl AsMut<[u32]> for BlockRngResults {
+    fn as_mut(&mut self) -> &mut [u32] {
+        &mut self.0
+    }
+}
+
+impl Default for BlockRngResults {
+    fn default() -> Self {
+        Self([0u32; BUFFER_SIZE])
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for BlockRngResults {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<R: Unsigned> SalsaCore<R> {
+    /// Fills the buffer with 4 consecutive keystream blocks.
+    fn generate(&mut self, buffer: &mut [u32; BUFFER_SIZE]) {
+        let mut blocks: [Block<Self>; BUF_BLOCKS] = Default::default();
+        self.write_keystream_blocks(&mut blocks);
+
+        let words = blocks.iter().flat_map(|block| block.chunks_exact(4));
+        for (word, chunk) in buffer.iter_mut().zip(words) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        #[cfg(feature = "zeroize")]
+        for block in blocks.iter_mut() {
+            block.zeroize();
+        }
+    }
+}
+
+macro_rules! impl_salsa_rng {
+    ($SalsaXRng:ident, $SalsaXCore:ident, $rounds:ident, $name:literal) => {
+        #[doc = concat!("A cryptographically secure random number generator that uses the ", $name, " algorithm.")]
+        ///
+        /// The RNG is seeded with a 256-bit key. The 64-bit nonce of the cipher is used as
+        /// a stream identifier, allowing 2<sup>64</sup> unique streams of output per seed,
+        /// while the 64-bit block counter allows 2<sup>64</sup> blocks of output per stream.
+        /// Both counter and stream are initialized to zero but may be set via the
+        /// `set_word_pos` and `set_stream` methods.
+        ///
+        /// This implementation uses an output buffer of four blocks, and uses
+        /// [`BlockRng`] to implement the [`RngCore`] methods.
+        ///
+        /// # Example
+        ///
+        /// ```rust
+        /// use salsa20::Salsa20Rng;
+        /// // use rand_core traits
+        /// use salsa20::rand_core::{RngCore, SeedableRng};
+        ///
+        /// let mut rng = Salsa20Rng::from_seed([42u8; 32]);
+        /// rng.set_stream(100);
+        /// rng.set_word_pos(5);
+        ///
+        /// let x = rng.next_u32();
+        /// let mut array = [0u8; 32];
+        /// rng.fill_bytes(&mut array);
+        /// ```
+        ///
+        /// The other RNGs from this crate are initialized similarly.
+        #[derive(Clone)]
+        #[cfg_attr(docsrs, doc(cfg(feature = "rng")))]
+        pub struct $SalsaXRng {
+            core: BlockRng<$SalsaXCore>,
+        }
+
+        #[doc = concat!("The ", $name, " core random number generator")]
+        #[derive(Clone)]
+        #[cfg_attr(docsrs, doc(cfg(feature = "rng")))]
+        pub struct $SalsaXCore(SalsaCore<$rounds>);
+
+        impl SeedableRng for $SalsaXRng {
+            type Seed = [u8; 32];
+
+            #[inline]
+            fn from_seed(seed: Self::Seed) -> Self {
+                Self {
+                    core: BlockRng::new($SalsaXCore::from_seed(seed)),
+                }
+            }
+        }
+
+        impl SeedableRng for $SalsaXCore {
+            type Seed = [u8; 32];
+
+            #[inline]
+            fn from_seed(seed: Self::Seed) -> Self {
+                Self(SalsaCore::new(&seed.into(), &Nonce::default()))
+            }
+        }
+
+        impl BlockRngCore for $SalsaXCore {
+            type Item = u32;
+            type Results = BlockRngResults;
+
+            #[inline]
+            fn generate(&mut self, r: &mut Self::Results) {
+                self.0.generate(&mut r.0);
+            }
+        }
+
+        impl CryptoBlockRng for $SalsaXCore {}
+        impl CryptoRng for $SalsaXRng {}
+
+        #[cfg(feature = "zeroize")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+        impl ZeroizeOnDrop for $SalsaXCore {}
+
+        #[cfg(feature = "zeroize")]
+        #[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+        impl ZeroizeOnDrop for $SalsaXRng {}
+
+        // Custom Debug implementation that does not expose the internal state
+        impl Debug for $SalsaXRng {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.write_str(concat!(stringify!($SalsaXRng), " { .. }"))
+            }
+        }
+
+        impl RngCore for $SalsaXRng {
+            #[inline]
+            fn next_u32(&mut self) -> u32 {
+                self.core.next_u32()
+            }
+            #[inline]
+            fn next_u64(&mut self) -> u64 {
+                self.core.next_u64()
+            }
+            #[inline]
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                self.core.fill_bytes(dest)
+            }
+        }
+
+        impl_try_rng_from_rng_core!($SalsaXRng);
+
+        impl $SalsaXRng {
+            // The buffer is a 4-block window, i.e. it is always at a block-aligned position in the
+            // stream but if the stream has been sought it may not be self-aligned.
+
+            /// Get the offset from the start of the stream, in 32-bit words.
+            ///
+            /// Since the generated blocks are 16 words (2<sup>4</sup>) long and the
+            /// counter is 64-bits, the offset is a 68-bit number. Sub-word offsets are
+            /// not supported, hence the result can simply be multiplied by 4 to get a
+            /// byte-offset.
+            #[inline]
+            pub fn get_word_pos(&self) -> u128 {
+                let block_pos = self.core.core.0.get_block_pos();
+                let buf_start = block_pos.wrapping_sub(BUF_BLOCKS as u64);
+                let result = (u128::from(buf_start) << 4) + self.core.index() as u128;
+                // eliminate bits above the 68th bit
+                result & WORD_POS_MASK
+            }
+
+            /// Set the offset from the start of the stream, in 32-bit words.
+            ///
+            /// As with `get_word_pos`, we use a 68-bit number: the least significant
+            /// 4 bits are used as the RNG's index, and the 64 bits before it as the
+            /// block position. Bits above the 68th bit are ignored.
+            #[inline]
+            pub fn set_word_pos(&mut self, word_offset: u128) {
+                let block_pos = (word_offset >> 4) as u64;
+                let index = (word_offset & 0b1111) as usize;
+                self.core.core.0.set_block_pos(block_pos);
+                // generate will increase block_pos by 4
+                self.core.generate_and_set(index);
+            }
+
+            /// Sets the block pos and resets the RNG's index.
+            ///
+            /// The word pos will be equal to `block_pos * 16 words per block`.
+            #[inline]
+            pub fn set_block_pos(&mut self, block_pos: u64) {
+                self.core.reset();
+                self.core.core.0.set_block_pos(block_pos);
+            }
+
+            /// Gets the block pos.
+            #[inline]
+            pub fn get_block_pos(&self) -> u64 {
+                self.core.core.0.get_block_pos()
+            }
+
+            /// Set the stream number.
+            ///
+            /// The stream number is used as the little-endian 64-bit nonce of the
+            /// cipher. This is initialized to zero; 2<sup>64</sup> unique streams of
+            /// output are available per seed/key.
+            #[inline]
+            pub fn set_stream(&mut self, stream: u64) {
+                self.core.core.0.state[6] = stream as u32;
+                self.core.core.0.state[7] = (stream >> 32) as u32;
+                if self.core.index() != BUFFER_SIZE {
+                    // regenerate the buffer at its current position with the new stream
+                    let block_pos = self.core.core.0.get_block_pos();
+                    self.core
+                        .core
+                        .0
+                        .set_block_pos(block_pos.wrapping_sub(BUF_BLOCKS as u64));
+                    self.core.generate_and_set(self.core.index());
+                }
+            }
+
+            /// Get the stream number.
+            #[inline]
+            pub fn get_stream(&self) -> u64 {
+                let state = &self.core.core.0.state;
+                u64::from(state[6]) | (u64::from(state[7]) << 32)
+            }
+
+            /// Get the seed.
+            #[inline]
+            pub fn get_seed(&self) -> [u8; 32] {
+                let state = &self.core.core.0.state;
+                let mut result = [0u8; 32];
+                let key_words = state[1..5].iter().chain(state[11..15].iter());
+                for (chunk, word) in result.chunks_exact_mut(4).zip(key_words) {
+                    chunk.copy_from_slice(&word.to_le_bytes());
+                }
+                result
+            }
+        }
+
+        impl PartialEq<$SalsaXRng> for $SalsaXRng {
+            fn eq(&self, rhs: &$SalsaXRng) -> bool {
+                self.get_seed() == rhs.get_seed()
+                    && self.get_stream() == rhs.get_stream()
+                    && self.get_word_pos() == rhs.get_word_pos()
+            }
+        }
+
+        impl Eq for $SalsaXRng {}
+
+        impl From<$SalsaXCore> for $SalsaXRng {
+            fn from(core: $SalsaXCore) -> Self {
+                $SalsaXRng {
+                    core: BlockRng::new(core),
+                }
+            }
+        }
+    };
+}
+
+impl_salsa_rng!(Salsa8Rng, Salsa8Core, U4, "Salsa20/8");
+
+impl_salsa_rng!(Salsa12Rng, Salsa12Core, U6, "Salsa20/12");
+
+impl_salsa_rng!(Salsa20Rng, Salsa20Core, U10, "Salsa20/20");
+
+/// The seed for [`XSalsa20Rng`]: a 256-bit key followed by a 192-bit nonce.
+///
+/// Implements `ZeroizeOnDrop` when the `zeroize` feature is enabled.
+#[cfg_attr(docsrs, doc(cfg(feature = "rng")))]
+pub struct XSalsa20Seed(pub [u8; 56]);
+
+impl Default for XSalsa20Seed {
+    fn default() -> Self {
+        Self([0u8; 56])
+    }
+}
+
+impl AsRef<[u8]> for XSalsa20Seed {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsMut<[u8]> for XSalsa20Seed {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for XSalsa20Seed {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for XSalsa20Seed {}
+
+/// A cryptographically secure random number generator that uses the XSalsa20 algorithm.
+///
+/// The RNG is seeded with a 256-bit key and a 192-bit nonce. As with the
+/// [`XSalsa20`][crate::XSalsa20] cipher, the first 128 bits of the nonce are used
+/// to derive a subkey with [`hsalsa`], and the remaining 64 bits select the stream
+/// of a [`Salsa20Rng`] seeded with that subkey.
+///
+/// # Example
+///
+/// ```rust
+/// use salsa20::XSalsa20Rng;
+/// use salsa20::rand_core::RngCore;
+///
+/// let mut rng = XSalsa20Rng::from_key_nonce([42u8; 32], [24u8; 24]);
+/// let x = rng.next_u32();
+/// ```
+#[derive(Clone)]
+#[cfg_attr(docsrs, doc(cfg(feature = "rng")))]
+pub struct XSalsa20Rng(Salsa20Rng);
+
+impl XSalsa20Rng {
+    /// Create a new RNG from a key and an extended nonce.
+    pub fn from_key_nonce(key: [u8; 32], nonce: [u8; 24]) -> Self {
+        #[cfg_attr(not(feature = "zeroize"), allow(unused_mut))]
+        let mut subkey = hsalsa::<U10>(&key.into(), nonce[..16].try_into().unwrap());
+        let mut rng = Salsa20Rng::from_seed(subkey.into());
+        rng.set_stream(u64::from_le_bytes(nonce[16..].try_into().unwrap()));
+
+        #[cfg(feature = "zeroize")]
+        subkey.zeroize();
+
+        Self(rng)
+    }
+
+    /// Get the offset from the start of the stream, in 32-bit words.
+    ///
+    /// See [`Salsa20Rng::get_word_pos`].
+    #[inline]
+    pub fn get_word_pos(&self) -> u128 {
+        self.0.get_word_pos()
+    }
+
+    /// Set the offset from the start of the stream, in 32-bit words.
+    ///
+    /// See [`Salsa20Rng::set_word_pos`].
+    #[inline]
+    pub fn set_word_pos(&mut self, word_offset: u128) {
+        self.0.set_word_pos(word_offset)
+    }
+}
+
+impl SeedableRng for XSalsa20Rng {
+    type Seed = XSalsa20Seed;
+
+    #[inline]
+    fn from_seed(seed: Self::Seed) -> Self {
+        let (key, nonce) = seed.0.split_at(32);
+        Self::from_key_nonce(key.try_into().unwrap(), nonce.try_into().unwrap())
+    }
+}
+
+impl RngCore for XSalsa20Rng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+}
+
+impl_try_rng_from_rng_core!(XSalsa20Rng);
+
+impl CryptoRng for XSalsa20Rng {}
+
+#[cfg(feature = "zeroize")]
+#[cfg_attr(docsrs, doc(cfg(feature = "zeroize")))]
+impl ZeroizeOnDrop for XSalsa20Rng {}
+
+// Custom Debug implementation that does not expose the internal state
+impl Debug for XSalsa20Rng {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("XSalsa20Rng { .. }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    //
+    // Test vectors from the eSTREAM Salsa20 KATs, also used in `tests/mod.rs`
+    //
+
+    const KEY1: [u8; 32] = hex!(
+        "80000000000000000000000000000000"
+        "00000000000000000000000000000000"
+    );
+
+    const KEY_LONG: [u8; 32] = hex!(
+        "0102030405060708090A0B0C0D0E0F10"
+        "1112131415161718191A1B1C1D1E1F20"
+    );
+
+    const IV_LONG: [u8; 8] = hex!("0301040105090206");
+
+    const EXPECTED_KEY1_IV0: [u8; 64] = hex!(
+        "e3be8fdd8beca2e3ea8ef9475b29a6e7"
+        "003951e1097a5c38d23b7a5fad9f6844"
+        "b22c97559e2723c7cbbd3fe4fc8d9a07"
+        "44652a83e72a9c461876af4d7ef1a117"
+    );
+
+    const EXPECTED_KEY0_IV1: [u8; 64] = hex!(
+        "2aba3dc45b4947007b14c851cd694456"
+        "b303ad59a465662803006705673d6c3e"
+        "29f1d3510dfc0405463c03414e0e07e3"
+        "59f1f1816c68b2434a19d3eee0464873"
+    );
+
+    const EXPECTED_LONG: [u8; 256] = hex!(
+        "6ebcbdbf76fccc64ab05542bee8a67cb"
+        "c28fa2e141fbefbb3a2f9b221909c8d7"
+        "d4295258cb539770dd24d7ac3443769f"
+        "fa27a50e60644264dc8b6b612683372e"
+        "085d0a12bf240b189ce2b78289862b56"
+        "fdc9fcffc33bef9325a2e81b98fb3fb9"
+        "aa04cf434615ceffeb985c1cb08d8440"
+        "e90b1d56ddeaea16d9e15affff1f698c"
+        "483c7a466af1fe062574adfd2b06a62b"
+        "4d98440719ea776385c470349a7ed696"
+        "9583463ed5d26b8fefccb205da0f5bfa"
+        "98c77812fe756b09eacc282aa42f4baf"
+        "a79633189046e2b20f35b3e0e54aa3b9"
+        "29e23c0f47dc7bcd4f928b2a9764be7d"
+        "4b8a50f980a50b35ad8087375e0c556e"
+        "cbe6a7161e8653ce9391e1e6710ed4f1"
+    );
+
+    #[test]
+    fn test_salsa_true_values() {
+        let mut rng = Salsa20Rng::from_seed(KEY1);
+        let mut buf = [0u8; 64];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, EXPECTED_KEY1_IV0);
+
+        let mut rng = Salsa20Rng::from_seed([0u8; 32]);
+        rng.set_stream(0x80);
+        let mut buf = [0u8; 64];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, EXPECTED_KEY0_IV1);
+
+        let mut rng = Salsa20Rng::from_seed(KEY_LONG);
+        rng.set_stream(u64::from_le_bytes(IV_LONG));
+        let mut buf = [0u8; 256];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, EXPECTED_LONG);
+    }
+
+    #[test]
+    fn test_salsa_word_pos() {
+        let mut rng = Salsa20Rng::from_seed(KEY_LONG);
+        rng.set_stream(u64::from_le_bytes(IV_LONG));
+        assert_eq!(rng.get_word_pos(), 0);
+
+        // word 37 is the 6th word of the third block
+        rng.set_word_pos(37);
+        assert_eq!(rng.get_word_pos(), 37);
+        assert_eq!(
+            rng.next_u32(),
+            u32::from_le_bytes(EXPECTED_LONG[148..152].try_into().unwrap())
+        );
+        assert_eq!(rng.get_word_pos(), 38);
+
+        rng.set_block_pos(3);
+        assert_eq!(rng.get_word_pos(), 48);
+        let mut buf = [0u8; 64];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, EXPECTED_LONG[192..]);
+
+        // test 64-bit counter wrap
+        let last_word = (1 << 68) - 1;
+        rng.set_word_pos(last_word);
+        assert_eq!(rng.get_word_pos(), last_word);
+        rng.next_u32();
+        assert_eq!(rng.get_word_pos(), 0);
+    }
+
+    #[test]
+    fn test_salsa_set_stream() {
+        let mut rng = Salsa20Rng::from_seed(KEY_LONG);
+        assert_eq!(rng.get_seed(), KEY_LONG);
+        let mut buf = [0u8; 60];
+        rng.fill_bytes(&mut buf);
+
+        // switching the stream part way through a block continues at the same position
+        rng.set_stream(u64::from_le_bytes(IV_LONG));
+        assert_eq!(rng.get_stream(), u64::from_le_bytes(IV_LONG));
+        assert_eq!(rng.get_word_pos(), 15);
+        let mut buf = [0u8; 8];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, EXPECTED_LONG[60..68]);
+    }
+
+    #[test]
+    fn test_xsalsa_true_values() {
+        const KEY: [u8; 32] = *b"this is 32-byte key for xsalsa20";
+        const NONCE: [u8; 24] = *b"24-byte nonce for xsalsa";
+        const EXPECTED: [u8; 64] = hex!(
+            "4848297feb1fb52fb66d81609bd547fa"
+            "bcbe7026edc8b5e5e449d088bfa69c08"
+            "8f5d8da1d791267c2c195a7f8cae9c4b"
+            "4050d08ce6d3a151ec265f3a58e47648"
+        );
+
+        let mut rng = XSalsa20Rng::from_key_nonce(KEY, NONCE);
+        let mut buf = [0u8; 64];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, EXPECTED);
+        assert_eq!(rng.get_word_pos(), 16);
+
+        let mut seed = XSalsa20Seed::default();
+        seed.as_mut()[..32].copy_from_slice(&KEY);
+        seed.as_mut()[32..].copy_from_slice(&NONCE);
+        let mut rng = XSalsa20Rng::from_seed(seed);
+        rng.set_word_pos(8);
+        let mut buf = [0u8; 32];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, EXPECTED[32..]);
+    }
+
+    #[test]
+    fn test_salsa_reduced_rounds() {
+        use crate::{Salsa12, Salsa8};
+        use cipher::StreamCipher;
+
+        let key = KEY_LONG.into();
+        let iv = IV_LONG.into();
+
+        let mut rng = Salsa12Rng::from_seed(KEY_LONG);
+        rng.set_stream(u64::from_le_bytes(IV_LONG));
+        let mut expected = [0u8; 100];
+        Salsa12::new(&key, &iv).apply_keystream(&mut expected);
+        let mut buf = [0u8; 100];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, expected);
+
+        let mut rng = Salsa8Rng::from_seed(KEY_LONG);
+        rng.set_stream(u64::from_le_bytes(IV_LONG));
+        let mut expected = [0u8; 100];
+        Salsa8::new(&key, &iv).apply_keystream(&mut expected);
+        let mut buf = [0u8; 100];
+        rng.fill_bytes(&mut buf);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_salsa_reduced_rounds_counter_wrap() {
+        macro_rules! check {
+            ($rng:ident) => {
+                let mut expected = $rng::from_seed(KEY_LONG);
+                let first = expected.next_u32();
+
+                let mut rng = $rng::from_seed(KEY_LONG);
+                let last_word = (1 << 68) - 1;
+                rng.set_word_pos(last_word);
+                assert_eq!(rng.get_word_pos(), last_word);
+                rng.next_u32();
+                assert_eq!(rng.get_word_pos(), 0);
+                assert_eq!(rng.next_u32(), first);
+            };
+        }
+
+        check!(Salsa8Rng);
+        check!(Salsa12Rng);
+        check!(Salsa20Rng);
+    }
+}
@@ -8,7 +8,7 @@ use cipher::{
     StreamCipherCoreWrapper, StreamCipherSeekCore,
 };
 
-use crate::backends::soft::quarter_round;
+use crate::backends::soft::double_rounds;
 
 #[cfg(feature = "zeroize")]
 use cipher::zeroize::ZeroizeOnDrop;
@@ -25,6 +25,7 @@ pub type XSalsa12 = StreamCipherCoreWrapper<XSalsaCore<U6>>;
 pub type XSalsa8 = StreamCipherCoreWrapper<XSalsaCore<U4>>;
 
 /// The XSalsa core function.
+#[derive(Clone)]
 pub struct XSalsaCore<R: Unsigned>(SalsaCore<R>);
 
 impl<R: Unsigned> KeySizeUser for XSalsaCore<R> {
@@ -43,9 +44,21 @@ impl<R: Unsigned> KeyIvInit for XSalsaCore<R> {
     #[inline]
     fn new(key: &Key, iv: &XNonce) -> Self {
         let subkey = hsalsa::<R>(key, iv[..16].try_into().unwrap());
-        let mut padded_iv = Nonce::default();
-        padded_iv.copy_from_slice(&iv[16..]);
-        XSalsaCore(SalsaCore::new(&subkey, &padded_iv))
+        Self::with_subkey(&subkey, iv[16..].try_into().unwrap())
+    }
+}
+
+impl<R: Unsigned> XSalsaCore<R> {
+    /// Create a core from a subkey computed by [`hsalsa`] over the first 16
+    /// bytes of the nonce, and the last 8 bytes of the nonce.
+    ///
+    /// The subkey only depends on the key and the nonce prefix, so messages
+    /// whose nonces share a prefix can reuse it instead of computing HSalsa
+    /// for every message. The result is the same as [`KeyIvInit::new`] with
+    /// the full nonce.
+    #[inline]
+    pub fn with_subkey(subkey: &Key, nonce_suffix: &[u8; 8]) -> Self {
+        XSalsaCore(SalsaCore::new(subkey, &Nonce::from(*nonce_suffix)))
     }
 }
 
@@ -115,19 +128,7 @@ pub fn hsalsa<R: Unsigned>(key: &Key, input: &Array<u8, U16>) -> Array<u8, U32>
     state[15] = CONSTANTS[3];
 
     // 20 rounds consisting of 10 column rounds and 10 diagonal rounds
-    for _ in 0..R::USIZE {
-        // column rounds
-        quarter_round(0, 4, 8, 12, &mut state);
-        quarter_round(5, 9, 13, 1, &mut state);
-        quarter_round(10, 14, 2, 6, &mut state);
-        quarter_round(15, 3, 7, 11, &mut state);
-
-        // diagonal rounds
-        quarter_round(0, 1, 2, 3, &mut state);
-        quarter_round(5, 6, 7, 4, &mut state);
-        quarter_round(10, 11, 8, 9, &mut state);
-        quarter_round(15, 12, 13, 14, &mut state);
-    }
+    double_rounds(&mut state, R::USIZE);
 
     let mut output = Array::default();
     let key_idx: [usize; 8] = [0, 5, 10, 15, 6, 7, 8, 9];
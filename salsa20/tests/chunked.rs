@@ -0,0 +1,8 @@
+//! Property tests checking that encrypting in arbitrary chunks matches
+//! encrypting in a single call.
+
+use salsa20::{Salsa20, XSalsa20};
+use stream_ciphers_test_helpers::chunked_test;
+
+chunked_test!(salsa20_chunked, Salsa20, key: 32, iv: 8);
+chunked_test!(xsalsa20_chunked, XSalsa20, key: 32, iv: 24);
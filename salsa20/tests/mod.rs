@@ -135,6 +135,67 @@ fn salsa20_long() {
     }
 }
 
+#[test]
+fn salsa20_new_with_counter() {
+    use salsa20::SalsaCore;
+
+    for counter in [0, 1, 3, u64::from(u32::MAX), 1 << 40] {
+        let mut expected = Salsa20::new(&KEY_LONG.into(), &IV_LONG.into());
+        expected.seek(u128::from(counter) * 64);
+        let mut expected_buf = [0u8; 64];
+        expected.apply_keystream(&mut expected_buf);
+
+        let core = SalsaCore::new_with_counter(&KEY_LONG.into(), &IV_LONG.into(), counter);
+        let mut cipher = Salsa20::from_core(core);
+        let mut buf = [0u8; 64];
+        cipher.apply_keystream(&mut buf);
+        assert_eq!(buf, expected_buf, "counter {counter}");
+    }
+}
+
+#[test]
+fn salsa20_new_from_slices() {
+    let mut cipher = Salsa20::new_from_slices(&KEY_LONG, &IV_LONG).unwrap();
+    let mut buf = [0; 256];
+    cipher.apply_keystream(&mut buf);
+    assert_eq!(buf, EXPECTED_LONG);
+
+    assert!(Salsa20::new_from_slices(&KEY_LONG, &IV_LONG[..7]).is_err());
+    assert!(Salsa20::new_from_slices(&KEY_LONG, &[0; IV_BYTES_XSALSA20]).is_err());
+    assert!(Salsa20::new_from_slices(&KEY_LONG[..16], &IV_LONG).is_err());
+}
+
+#[test]
+fn salsa20_core_round_trip() {
+    use salsa20::{PartialBlockError, SalsaCore};
+
+    let mut cipher = Salsa20::new(&KEY_LONG.into(), &IV_LONG.into());
+    let mut buf = [0; 256];
+    cipher.apply_keystream(&mut buf[..100]);
+    assert_eq!(
+        SalsaCore::try_from(cipher.clone()).err(),
+        Some(PartialBlockError)
+    );
+
+    cipher.apply_keystream(&mut buf[100..128]);
+    let core = SalsaCore::try_from(cipher).unwrap();
+    let mut cipher = Salsa20::from_core(core);
+    cipher.apply_keystream(&mut buf[128..]);
+    assert_eq!(buf, EXPECTED_LONG);
+}
+
+#[test]
+fn salsa20_byte_at_a_time() {
+    let mut cipher = Salsa20::new(&KEY_LONG.into(), &IV_LONG.into());
+    let mut buf = [0; 256];
+
+    for byte in buf.chunks_mut(1) {
+        cipher.try_apply_keystream(byte).unwrap();
+    }
+
+    assert_eq!(buf, EXPECTED_LONG);
+}
+
 #[test]
 #[ignore]
 fn salsa20_offsets() {
@@ -156,6 +217,183 @@ fn salsa20_offsets() {
     }
 }
 
+#[test]
+fn salsa20_128_key() {
+    use salsa20::Salsa20_128;
+
+    // eSTREAM Salsa20 128-bit key test vectors, set 1, vector 0
+    const KEY: [u8; 16] = hex!("80000000000000000000000000000000");
+    const EXPECTED_0: [u8; 64] = hex!(
+        "4DFA5E481DA23EA09A31022050859936DA52FCEE218005164F267CB65F5CFD7F"
+        "2B4F97E0FF16924A52DF269515110A07F9E460BC65EF95DA58F740B7D1DBB0AA"
+    );
+    const EXPECTED_192: [u8; 64] = hex!(
+        "DA9C1581F429E0A00F7D67E23B730676783B262E8EB43A25F55FB90B3E753AEF"
+        "8C6713EC66C51881111593CCB3E8CB8F8DE124080501EEEB389C4BCB6977CF95"
+    );
+
+    let mut cipher = Salsa20_128::new(&KEY.into(), &IV0.into());
+    let mut buf = [0; 256];
+    cipher.apply_keystream(&mut buf);
+    assert_eq!(buf[..64], EXPECTED_0);
+    assert_eq!(buf[192..], EXPECTED_192);
+}
+
+#[test]
+fn salsa20_soft_rounds() {
+    // "expand 32-byte k"
+    const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+    let key = |i: usize| u32::from_le_bytes(KEY_LONG[4 * i..][..4].try_into().unwrap());
+    let iv = |i: usize| u32::from_le_bytes(IV_LONG[4 * i..][..4].try_into().unwrap());
+
+    for block in 0..4 {
+        #[rustfmt::skip]
+        let state = [
+            CONSTANTS[0], key(0), key(1), key(2),
+            key(3), CONSTANTS[1], iv(0), iv(1),
+            block, 0, CONSTANTS[2], key(4),
+            key(5), key(6), key(7), CONSTANTS[3],
+        ];
+        let mut res = state;
+        salsa20::soft_rounds(&mut res, 10);
+
+        let mut buf = [0u8; 64];
+        for ((chunk, r), s) in buf.chunks_exact_mut(4).zip(res).zip(state) {
+            chunk.copy_from_slice(&r.wrapping_add(s).to_le_bytes());
+        }
+        assert_eq!(buf[..], EXPECTED_LONG[block as usize * 64..][..64]);
+    }
+}
+
+#[test]
+fn salsa20_seek_block() {
+    let mut cipher = Salsa20::new(&KEY_LONG.into(), &IV_LONG.into());
+    let mut buf = [0; 256];
+    cipher.apply_keystream(&mut buf);
+
+    for block in 0..4u64 {
+        let mut cipher = Salsa20::new(&KEY_LONG.into(), &IV_LONG.into());
+        cipher.seek(block * 64);
+        assert_eq!(cipher.current_pos::<u64>() / 64, block);
+
+        let mut block_buf = [0; 64];
+        cipher.apply_keystream(&mut block_buf);
+        assert_eq!(block_buf[..], buf[block as usize * 64..][..64]);
+        assert_eq!(cipher.current_pos::<u64>() / 64, block + 1);
+    }
+}
+
+#[test]
+fn salsa20_keystream_range() {
+    use salsa20::{cipher::consts::U10, SalsaCore};
+
+    let mut keystream = [0; 256];
+    Salsa20::new(&KEY_LONG.into(), &IV_LONG.into()).apply_keystream(&mut keystream);
+
+    for start in [0, 1, 63, 64, 65, 100, 128] {
+        for len in [0, 1, 64, 100, 256 - start] {
+            let end = start + len;
+            if end > keystream.len() {
+                continue;
+            }
+            let mut buf = [0xFF; 256];
+            let buf = &mut buf[..len];
+            SalsaCore::<U10>::keystream_range(&KEY_LONG.into(), &IV_LONG.into(), start as u64, buf);
+            assert_eq!(buf, &keystream[start..end]);
+        }
+    }
+}
+
+#[test]
+fn salsa20_write_keystream() {
+    use salsa20::{
+        cipher::{typenum::U10, StreamCipherCore, StreamCipherSeekCore},
+        SalsaCore,
+    };
+
+    let mut cipher = Salsa20::new(&KEY_LONG.into(), &IV_LONG.into());
+    let mut buf = [0; 100];
+    cipher.apply_keystream(&mut buf);
+
+    let mut core = SalsaCore::<U10>::new(&KEY_LONG.into(), &IV_LONG.into());
+    let mut blocks = [Default::default(); 2];
+    core.write_keystream_blocks(&mut blocks);
+    assert_eq!(buf[..64], blocks[0][..]);
+    assert_eq!(buf[64..], blocks[1][..36]);
+
+    // both advance the counter past the partially used block
+    assert_eq!(core.get_block_pos(), 2);
+    assert_eq!(cipher.current_pos::<u64>(), 100);
+    let mut block = [0; 64];
+    cipher.seek(128u64);
+    cipher.apply_keystream(&mut block);
+    let mut core_block = Default::default();
+    core.write_keystream_block(&mut core_block);
+    assert_eq!(block, core_block[..]);
+}
+
+#[test]
+fn salsa20_counter_carry() {
+    let mut cipher = Salsa20::new(&KEY_LONG.into(), &IV_LONG.into());
+    let mut buf1 = [0; 128];
+    cipher.seek((u64::from(u32::MAX)) * 64);
+    cipher.apply_keystream(&mut buf1);
+
+    let mut buf2 = [0; 64];
+    cipher.seek((u64::from(u32::MAX) + 1) * 64);
+    cipher.apply_keystream(&mut buf2);
+    assert_eq!(buf1[64..], buf2);
+}
+
+#[test]
+fn salsa20_clone() {
+    use salsa20::{Salsa12, Salsa8};
+
+    fn check(mut cipher: impl StreamCipher + Clone) {
+        cipher.apply_keystream(&mut [0; 100]);
+
+        let mut clone = cipher.clone();
+        let mut buf1 = [0; 256];
+        cipher.apply_keystream(&mut buf1);
+        let mut buf2 = [0; 256];
+        clone.apply_keystream(&mut buf2);
+        assert_eq!(buf1, buf2);
+    }
+
+    check(Salsa20::new(&KEY_LONG.into(), &IV_LONG.into()));
+    check(Salsa12::new(&KEY_LONG.into(), &IV_LONG.into()));
+    check(Salsa8::new(&KEY_LONG.into(), &IV_LONG.into()));
+    check(XSalsa20::new(&KEY_LONG.into(), &Default::default()));
+}
+
+#[test]
+fn salsa_reduced_rounds_split() {
+    use salsa20::{Salsa12, Salsa8};
+
+    let mut buf1 = [0; 256];
+    let mut cipher = Salsa12::new(&KEY_LONG.into(), &IV_LONG.into());
+    cipher.apply_keystream(&mut buf1);
+
+    let mut buf2 = [0; 256];
+    let mut cipher = Salsa12::new(&KEY_LONG.into(), &IV_LONG.into());
+    for chunk in buf2.chunks_mut(64) {
+        cipher.apply_keystream(chunk);
+    }
+    assert_eq!(buf1, buf2);
+
+    let mut buf1 = [0; 256];
+    let mut cipher = Salsa8::new(&KEY_LONG.into(), &IV_LONG.into());
+    cipher.apply_keystream(&mut buf1);
+
+    let mut buf2 = [0; 256];
+    let mut cipher = Salsa8::new(&KEY_LONG.into(), &IV_LONG.into());
+    for chunk in buf2.chunks_mut(64) {
+        cipher.apply_keystream(chunk);
+    }
+    assert_eq!(buf1, buf2);
+}
+
 #[test]
 fn xsalsa20_encrypt_zeros() {
     let mut cipher = XSalsa20::new(&KEY_XSALSA20.into(), &IV_XSALSA20.into());
@@ -176,6 +414,20 @@ fn xsalsa20_encrypt_hello_world() {
     assert_eq!(buf, EXPECTED_XSALSA20_HELLO_WORLD);
 }
 
+#[test]
+fn xsalsa20_with_subkey() {
+    use cipher::consts::U10;
+    use salsa20::{hsalsa, XSalsaCore};
+
+    let subkey = hsalsa::<U10>(&KEY_XSALSA20.into(), IV_XSALSA20[..16].try_into().unwrap());
+    let core = XSalsaCore::<U10>::with_subkey(&subkey, IV_XSALSA20[16..].try_into().unwrap());
+    let mut cipher = XSalsa20::from_core(core);
+    let mut buf = [0; 64];
+    cipher.apply_keystream(&mut buf);
+
+    assert_eq!(buf, EXPECTED_XSALSA20_ZEROS);
+}
+
 #[test]
 fn salsa20_regression_2024_03() {
     use salsa20::{
@@ -195,3 +447,36 @@ fn salsa20_regression_2024_03() {
 
     assert_eq!(x, hex!("66a3d4a32f86eb8eaefe5aa25cb5ff1aac91177dd03f114979d042f15658a505035b90d1559f1dd0c2ceaf3014129729fdd697cf94d16116588b271cd03d9b42"));
 }
+
+#[cfg(feature = "zeroize")]
+#[test]
+fn salsa_zeroize_on_drop() {
+    use salsa20::{
+        cipher::{
+            typenum::{U10, U4, U6},
+            zeroize::ZeroizeOnDrop,
+        },
+        SalsaCore, XSalsaCore,
+    };
+
+    fn assert_zeroize_on_drop<T: ZeroizeOnDrop>() {}
+
+    assert_zeroize_on_drop::<SalsaCore<U4>>();
+    assert_zeroize_on_drop::<SalsaCore<U6>>();
+    assert_zeroize_on_drop::<SalsaCore<U10>>();
+    assert_zeroize_on_drop::<XSalsaCore<U4>>();
+    assert_zeroize_on_drop::<XSalsaCore<U6>>();
+    assert_zeroize_on_drop::<XSalsaCore<U10>>();
+}
+
+#[test]
+#[cfg(feature = "rng")]
+fn salsa20_rng_debug_is_redacted() {
+    use salsa20::{rand_core::SeedableRng, Salsa20Rng, XSalsa20Rng, XSalsa20Seed};
+
+    let rng = Salsa20Rng::from_seed([0x42; 32]);
+    assert_eq!(format!("{:?}", rng), "Salsa20Rng { .. }");
+
+    let rng = XSalsa20Rng::from_seed(XSalsa20Seed([0x42; 56]));
+    assert_eq!(format!("{:?}", rng), "XSalsa20Rng { .. }");
+}
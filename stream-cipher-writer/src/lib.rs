@@ -0,0 +1,113 @@
+#![cfg_attr(docsrs, feature(doc_cfg))]
+#![doc = include_str!("../README.md")]
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg",
+    html_favicon_url = "https://raw.githubusercontent.com/RustCrypto/media/8f1a9894/logo.svg"
+)]
+#![forbid(unsafe_code)]
+#![warn(missing_docs, rust_2018_idioms)]
+
+pub use cipher;
+
+use cipher::{StreamCipher, StreamCipherSeek};
+use std::io;
+
+/// Size of the stack buffer holding the ciphertext of a single write.
+const SCRATCH_SIZE: usize = 4096;
+
+/// Writer which applies the keystream of a stream cipher to all written
+/// data before forwarding it to an inner writer.
+///
+/// Every call to [`write`][io::Write::write] forwards at most one chunk of
+/// ciphertext to the inner writer. If the inner writer accepts only part of
+/// it, the cipher is rewound to the position right after the accepted bytes,
+/// so the keystream stays aligned with the data which was actually written.
+///
+/// # Example
+///
+/// ```
+/// use chacha20::ChaCha20;
+/// use stream_cipher_writer::StreamCipherWriter;
+/// use chacha20::cipher::{KeyIvInit, StreamCipher};
+/// use std::io::Write;
+///
+/// let key = [0x42; 32];
+/// let nonce = [0x24; 12];
+///
+/// let mut writer = StreamCipherWriter::new(ChaCha20::new(&key.into(), &nonce.into()), Vec::new());
+/// writer.write_all(b"hello ").unwrap();
+/// writer.write_all(b"world").unwrap();
+/// let (_, ciphertext) = writer.into_inner();
+///
+/// let mut buf = *b"hello world";
+/// ChaCha20::new(&key.into(), &nonce.into()).apply_keystream(&mut buf);
+/// assert_eq!(ciphertext, buf);
+/// ```
+pub struct StreamCipherWriter<C, W> {
+    cipher: C,
+    inner: W,
+}
+
+impl<C, W> StreamCipherWriter<C, W>
+where
+    C: StreamCipher + StreamCipherSeek,
+    W: io::Write,
+{
+    /// Create a new writer encrypting data with `cipher` and writing the
+    /// result to `inner`.
+    pub fn new(cipher: C, inner: W) -> Self {
+        Self { cipher, inner }
+    }
+
+    /// Get a reference to the cipher.
+    pub fn cipher(&self) -> &C {
+        &self.cipher
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.
+    ///
+    /// Writing to the inner writer directly bypasses the cipher.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap this writer, returning the cipher and the inner writer.
+    pub fn into_inner(self) -> (C, W) {
+        (self.cipher, self.inner)
+    }
+}
+
+impl<C, W> io::Write for StreamCipherWriter<C, W>
+where
+    C: StreamCipher + StreamCipherSeek,
+    W: io::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut scratch = [0u8; SCRATCH_SIZE];
+        let len = buf.len().min(SCRATCH_SIZE);
+        let scratch = &mut scratch[..len];
+
+        let pos: u128 = self.cipher.current_pos();
+        scratch.copy_from_slice(&buf[..len]);
+        self.cipher
+            .try_apply_keystream(scratch)
+            .map_err(|_| io::Error::other("end of keystream reached"))?;
+
+        let res = self.inner.write(scratch);
+        let written = *res.as_ref().unwrap_or(&0);
+        if written != len {
+            // rewind the keystream to the end of the accepted data
+            self.cipher.seek(pos + written as u128);
+        }
+        res
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,62 @@
+use std::io::{self, Write};
+use stream_cipher_writer::{
+    cipher::{KeyIvInit, StreamCipher, StreamCipherSeek},
+    StreamCipherWriter,
+};
+
+/// Writer accepting at most `max` bytes per call.
+struct ShortWriter {
+    out: Vec<u8>,
+    max: usize,
+}
+
+impl Write for ShortWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.max);
+        self.out.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Write `plaintext` in chunks through writers accepting short writes, and
+/// compare the result with a single bulk encryption.
+fn check_chunked_writes<C>(new: impl Fn() -> C)
+where
+    C: StreamCipher + StreamCipherSeek,
+{
+    let plaintext: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+    let mut expected = plaintext.clone();
+    new().apply_keystream(&mut expected);
+
+    for chunk_size in [1, 3, 63, 64, 65, 5000] {
+        for max in [1, 7, 64, 100, usize::MAX] {
+            let inner = ShortWriter {
+                out: Vec::new(),
+                max,
+            };
+            let mut writer = StreamCipherWriter::new(new(), inner);
+            for chunk in plaintext.chunks(chunk_size) {
+                writer.write_all(chunk).unwrap();
+            }
+            writer.flush().unwrap();
+
+            let (cipher, inner) = writer.into_inner();
+            assert_eq!(inner.out, expected, "chunk size {chunk_size}, max {max}");
+            assert_eq!(cipher.current_pos::<usize>(), plaintext.len());
+        }
+    }
+}
+
+#[test]
+fn chacha20_chunked_writes() {
+    check_chunked_writes(|| chacha20::ChaCha20::new(&[0x42; 32].into(), &[0x24; 12].into()));
+}
+
+#[test]
+fn salsa20_chunked_writes() {
+    check_chunked_writes(|| salsa20::Salsa20::new(&[0x42; 32].into(), &[0x24; 8].into()));
+}
@@ -0,0 +1,78 @@
+//! Test helpers shared by the stream-cipher crates of this workspace.
+
+pub use cipher;
+pub use proptest;
+
+use cipher::StreamCipher;
+use proptest::{prop_assert_eq, test_runner::TestCaseError};
+
+/// Apply the keystream of a fresh `new()` cipher to `data` both in a single
+/// call and in chunks of `sizes` (repeated until the data runs out), then check
+/// that the results agree and that decrypting restores `data`.
+pub fn check_chunked<C: StreamCipher>(
+    new: impl Fn() -> C,
+    data: &[u8],
+    sizes: &[usize],
+) -> Result<(), TestCaseError> {
+    let mut expected = data.to_vec();
+    new().apply_keystream(&mut expected);
+
+    let mut actual = data.to_vec();
+    let mut cipher = new();
+    let mut rest = &mut actual[..];
+    for &n in sizes.iter().cycle() {
+        if rest.is_empty() {
+            break;
+        }
+        let (chunk, tail) = rest.split_at_mut(n.min(rest.len()));
+        cipher.apply_keystream(chunk);
+        rest = tail;
+    }
+    prop_assert_eq!(&actual, &expected);
+
+    new().apply_keystream(&mut actual);
+    prop_assert_eq!(&actual[..], data);
+    Ok(())
+}
+
+/// Define a property test checking [`check_chunked`] for a cipher with random
+/// keys (and IVs), data and chunk sizes.
+///
+/// The cipher is constructed with `KeyInit` when only a key size is given, and
+/// with `KeyIvInit` otherwise.
+#[macro_export]
+macro_rules! chunked_test {
+    ($name:ident, $cipher:ty, key: $key_len:literal) => {
+        $crate::proptest::proptest! {
+            #[test]
+            fn $name(
+                key in $crate::proptest::prelude::any::<[u8; $key_len]>(),
+                data in $crate::proptest::collection::vec($crate::proptest::prelude::any::<u8>(), 0..2048),
+                sizes in $crate::proptest::collection::vec(1usize..300, 1..16),
+            ) {
+                $crate::check_chunked(
+                    || <$cipher as $crate::cipher::KeyInit>::new(&key.into()),
+                    &data,
+                    &sizes,
+                )?;
+            }
+        }
+    };
+    ($name:ident, $cipher:ty, key: $key_len:literal, iv: $iv_len:literal) => {
+        $crate::proptest::proptest! {
+            #[test]
+            fn $name(
+                key in $crate::proptest::prelude::any::<[u8; $key_len]>(),
+                iv in $crate::proptest::prelude::any::<[u8; $iv_len]>(),
+                data in $crate::proptest::collection::vec($crate::proptest::prelude::any::<u8>(), 0..2048),
+                sizes in $crate::proptest::collection::vec(1usize..300, 1..16),
+            ) {
+                $crate::check_chunked(
+                    || <$cipher as $crate::cipher::KeyIvInit>::new(&key.into(), &iv.into()),
+                    &data,
+                    &sizes,
+                )?;
+            }
+        }
+    };
+}